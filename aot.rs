@@ -0,0 +1,206 @@
+//! # Translated Images
+//!
+//! Images can be translated ahead of time into Rust source with `translate`,
+//! or the **eforth-aot** binary, for deploying a fixed Forth application. The
+//! translated code runs on a `Machine`, which executes each instruction with
+//! the same code as the interpreter, specialised by the compiler for each
+//! instruction as they are constants in the translated source.
+//!
+//! A module using the types here can be generated from an image and built
+//! into a program with a build script, or generated once and kept:
+//!
+//! ```no_run
+//! let vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+//! let cells: Vec<u16> = (0..0x1000).map(|a| vm.read(a)).collect();
+//! let source = embed::aot::translate(&cells, "embed::aot");
+//! std::fs::write("eforth_aot.rs", source).unwrap();
+//! ```
+//!
+//! With the `eforth` feature the bundled eForth image is translated by the
+//! build script and available as `aot::eforth`. Translated code does not trace
+//! or count instructions.
+
+use decode::Op;
+pub use host::{Input, Output, Store};
+pub use {Outcome, VM};
+pub use translate::{blocks, translate};
+use Registers;
+
+/// `eforth` is the bundled eForth image, translated.
+#[cfg(feature = "eforth")]
+pub mod eforth {
+	include!(concat!(env!("OUT_DIR"), "/eforth_aot.rs"));
+}
+
+/// `Machine` is the state translated code runs on, a virtual machine along
+/// with its input and output.
+pub struct Machine<'a> {
+	vm: &'a mut VM,
+	r: Registers,
+	code: &'a [u64],
+	store: &'a mut dyn Store,
+	input: &'a mut dyn Input,
+	output: &'a mut dyn Output,
+	outcome: Option<Outcome>,
+	modified: bool,
+}
+
+/// `translated` is true if `code` has the bit for cell `a` set.
+fn translated(code: &[u64], a: usize) -> bool {
+	code.get(a / 64).is_some_and(|c| c & (1 << (a % 64)) != 0)
+}
+
+impl<'a> Machine<'a> {
+	/// `new` creates a machine running `vm` with translated code, `code` has
+	/// a bit set for each cell that has been translated and `image` holds
+	/// the cells as they were translated. If any translated cell in `vm` no
+	/// longer matches `image`, as it was written to since, the translated
+	/// code is not used.
+	pub fn new(vm: &'a mut VM, code: &'a [u64], image: &[u16], store: &'a mut dyn Store, input: &'a mut dyn Input, output: &'a mut dyn Output) -> Self {
+		let r = vm.registers();
+		let modified = (0..code.len() * 64).any(|a| translated(code, a) && image.get(a) != Some(&vm.core[a]));
+		Machine { vm, r, code, store, input, output, outcome: None, modified }
+	}
+
+	/// `pc` returns the program counter.
+	pub fn pc(&self) -> u16 { self.r.pc }
+
+	/// `running` is true until the machine halts or needs input.
+	pub fn running(&self) -> bool { self.outcome.is_none() }
+
+	/// `translated` is true until a translated cell is written to, after which
+	/// the translated code may no longer match memory.
+	pub fn translated(&self) -> bool { !self.modified }
+
+	/// `op` executes `instruction`, which must be the instruction at the
+	/// program counter.
+	///
+	/// # Returns
+	///
+	/// True if the rest of the block must not be run, as the machine has
+	/// halted or needs input, or a translated cell has been written to.
+	#[inline(always)]
+	pub fn op(&mut self, instruction: u16) -> bool {
+		let op = Op::decode(instruction);
+		if op.reads_input() && !self.input.available() {
+			self.outcome = Some(Outcome::NeedInput);
+			return true;
+		}
+		let (code, modified) = (self.code, &mut self.modified);
+		let mut written = |a: u16| if translated(code, a as usize) { *modified = true };
+		if VM::exec(op, &mut self.r, &mut self.vm.core, self.vm.order, self.store, self.input, self.output, &mut written) {
+			self.outcome = Some(Outcome::Halted((self.r.t as i16) as i32));
+		}
+		self.outcome.is_some() || self.modified
+	}
+
+	/// `interpret` executes the instruction at the program counter, for code
+	/// that has not been translated.
+	pub fn interpret(&mut self) {
+		let instruction = self.vm.core[self.r.pc as usize];
+		self.op(instruction);
+	}
+
+	/// `finish` stores the registers back into the virtual machine.
+	///
+	/// # Returns
+	///
+	/// `Outcome::Halted` or `Outcome::NeedInput`, as `VM::step` would, or
+	/// `Outcome::Running` if the machine has not been run.
+	pub fn finish(self) -> Outcome {
+		self.vm.set_registers(self.r);
+		self.outcome.unwrap_or(Outcome::Running)
+	}
+}
+
+#[cfg(all(test, feature = "eforth"))]
+mod tests {
+	use super::*;
+
+	/// `Lines` has input available a line at a time.
+	struct Lines<'a>(&'a [u8], bool);
+
+	impl<'a> Input for Lines<'a> {
+		fn getc(&mut self) -> Option<u8> {
+			let (&c, rest) = self.0.split_first()?;
+			self.0 = rest;
+			self.1 = c != b'\n';
+			Some(c)
+		}
+		fn available(&mut self) -> bool { self.1 }
+	}
+
+	#[test]
+	fn eforth() {
+		let source = b"decimal : fib dup 2 < if exit then dup 1- recurse swap 2 - recurse + ;\n\
+			15 fib . words\n7 0 /mod . .\n' fib cell+ @ . 9 (bye)\n";
+		let mut plain = VM::new();
+		let mut output = Vec::new();
+		let code = plain.execute(&mut (), &mut &source[..], &mut output);
+		assert_eq!(code, 9, "{}", String::from_utf8_lossy(&output));
+
+		let mut vm = VM::new();
+		let mut translated = Vec::new();
+		assert_eq!(eforth::execute(&mut vm, &mut (), &mut &source[..], &mut translated), Outcome::Halted(9));
+		assert_eq!(translated, output);
+		assert_eq!(vm.core[..], plain.core[..]);
+		assert_eq!(vm.registers(), plain.registers());
+
+		/* resumed a line at a time */
+		let (mut vm, mut store, mut input, mut lines) = (VM::new(), (), Lines(&source[..], true), Vec::new());
+		let mut outcome = Outcome::NeedInput;
+		while outcome == Outcome::NeedInput {
+			input.1 = true;
+			let mut m = Machine::new(&mut vm, &eforth::CODE, &eforth::IMAGE, &mut store, &mut input, &mut lines);
+			eforth::run(&mut m);
+			assert!(m.translated());
+			outcome = m.finish();
+		}
+		assert_eq!(outcome, Outcome::Halted(9));
+		assert_eq!(lines, output);
+		assert_eq!(vm.core[..], plain.core[..]);
+	}
+
+	#[test]
+	fn modified() {
+		/* writing to the reset vector, even without changing it, falls back to the interpreter */
+		let source = b"0 @ 0 ! 2 3 + . 0 (bye)\n";
+		let mut plain = VM::new();
+		let mut output = Vec::new();
+		plain.execute(&mut (), &mut &source[..], &mut output);
+
+		let (mut vm, mut store, mut input, mut translated) = (VM::new(), (), &source[..], Vec::new());
+		let mut m = Machine::new(&mut vm, &eforth::CODE, &eforth::IMAGE, &mut store, &mut input, &mut translated);
+		eforth::run(&mut m);
+		assert!(!m.translated());
+		assert_eq!(m.finish(), Outcome::Halted(0));
+		assert_eq!(translated, output);
+		assert_eq!(vm.core[..], plain.core[..]);
+	}
+
+	#[test]
+	fn patched() {
+		/* memory patched between calls is not run from stale translated code */
+		let (mut plain, mut vm) = (VM::new(), VM::new());
+		plain.write(0, 0x7b00);
+		vm.write(0, 0x7b00);
+		assert_eq!(plain.step(u64::MAX, &mut (), &mut (), &mut ()), Outcome::Halted(0));
+		assert_eq!(eforth::execute(&mut vm, &mut (), &mut (), &mut ()), Outcome::Halted(0));
+
+		/* nor is it once the machine is resumed after waiting for input */
+		let source = b"2 3 + . 0 (bye)\n";
+		let (mut plain, mut vm) = (VM::new(), VM::new());
+		let (mut a, mut b) = (Vec::new(), Vec::new());
+		assert_eq!(plain.step(u64::MAX, &mut (), &mut Lines(b"", false), &mut a), Outcome::NeedInput);
+		assert_eq!(eforth::execute(&mut vm, &mut (), &mut Lines(b"", false), &mut b), Outcome::NeedInput);
+		assert_eq!(plain.pc, vm.pc);
+		let pc = plain.pc;
+		plain.write(pc, 0x7b00);
+		vm.write(pc, 0x7b00);
+		let code = plain.execute(&mut (), &mut &source[..], &mut a);
+		assert_eq!(eforth::execute(&mut vm, &mut (), &mut &source[..], &mut b), Outcome::Halted(code));
+		assert_eq!(b, a);
+		assert_eq!(vm.core[..], plain.core[..]);
+		assert_eq!(vm.registers(), plain.registers());
+	}
+}
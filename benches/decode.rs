@@ -0,0 +1,48 @@
+//! Compares the plain interpreter, `VM::step`, with the interpreters running
+//! from a cache of decoded instructions, `Predecoded::step`, and of fused
+//! superinstructions, `Fused::step`, on the eForth
+//! image running a compute heavy word forever. Throughput is reported in
+//! instructions per second, divide by a million for MIPS.
+
+extern crate criterion;
+extern crate embed;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use embed::decode::Predecoded;
+use embed::fuse::Fused;
+use embed::{Outcome, VM};
+
+/// `CYCLES` is the number of instructions executed per iteration.
+const CYCLES: u64 = 1_000_000;
+
+/// `SPIN` defines a recursive Fibonacci word and calls it in a loop that
+/// never ends, so the virtual machine never waits on input.
+const SPIN: &[u8] = b"decimal : fib dup 2 < if exit then dup 1- recurse swap 2 - recurse + ;\n: spin begin 20 fib drop again ; spin\n";
+
+/// `spinning` returns the eForth image once it has started running `SPIN`.
+fn spinning() -> VM {
+	let mut vm = VM::new();
+	let mut input = SPIN;
+	assert_eq!(vm.step(10_000_000, &mut (), &mut input, &mut ()), Outcome::Running);
+	assert!(input.is_empty());
+	vm
+}
+
+fn interpreters(c: &mut Criterion) {
+	let mut group = c.benchmark_group("interpreter");
+	group.throughput(Throughput::Elements(CYCLES));
+
+	let mut vm = spinning();
+	group.bench_function("step", |b| b.iter(|| vm.step(CYCLES, &mut (), &mut (), &mut ())));
+
+	let mut fast = Predecoded::new(spinning());
+	group.bench_function("predecoded", |b| b.iter(|| fast.step(CYCLES, &mut (), &mut (), &mut ())));
+
+	let mut fused = Fused::new(spinning());
+	group.bench_function("fused", |b| b.iter(|| fused.step(CYCLES, &mut (), &mut (), &mut ())));
+
+	group.finish();
+}
+
+criterion_group!(benches, interpreters);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+//! Benchmarks of the eForth image running on `VM::execute`, for catching
+//! regressions in the interpreter loop and for comparison with the C embed
+//! virtual machine running the same Forth. Every benchmark boots a fresh
+//! copy of the bundled image, which prints its banner, then reads a script
+//! ending in `(bye)`, except for the raw instruction throughput which keeps
+//! one machine running a loop of primitives forever.
+//!
+//! * `throughput` executes `CYCLES` instructions of a loop, reported in
+//!   instructions per second
+//! * `boot` is only the banner and `(bye)`, the overhead included in the rest
+//! * `dictionary` searches the dictionary with `find` for a word near the
+//!   start of it and for one that does not exist, and lists it with `words`
+//! * `format` prints numbers with `.` and with `u.` in hexadecimal
+//! * `compile` compiles a few hundred colon definitions
+
+extern crate criterion;
+extern crate embed;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use embed::{Outcome, VM};
+
+/// `CYCLES` is the number of instructions executed per iteration of the
+/// throughput benchmark.
+const CYCLES: u64 = 1_000_000;
+
+/// `REPEAT` is the number of times a word is looked up, or a number printed,
+/// per iteration.
+const REPEAT: u64 = 1000;
+
+/// `DEFINITIONS` is the number of words compiled per iteration.
+const DEFINITIONS: u64 = 300;
+
+/// `SPIN` is a loop of stack, arithmetic and memory primitives that never
+/// ends, so the virtual machine never waits on input.
+const SPIN: &[u8] = b": spin begin 1 2 + dup 3 and swap over - 2drop here @ drop again ; spin\n";
+
+/// `run` boots the eForth image and runs `source`, which must end the
+/// session with `(bye)`.
+fn run(source: &[u8]) {
+	let mut vm = VM::new();
+	assert_eq!(vm.execute(&mut (), &mut &source[..], &mut ()), 0);
+}
+
+/// `bench` adds a benchmark running `source` on a fresh image to `group`.
+fn bench(group: &mut criterion::BenchmarkGroup<criterion::measurement::WallTime>, name: &str, source: &[u8]) {
+	group.bench_function(name, |b| b.iter(|| run(source)));
+}
+
+fn throughput(c: &mut Criterion) {
+	let mut group = c.benchmark_group("throughput");
+	group.throughput(Throughput::Elements(CYCLES));
+	let mut vm = VM::new();
+	let mut input = SPIN;
+	assert_eq!(vm.step(10_000_000, &mut (), &mut input, &mut ()), Outcome::Running);
+	assert!(input.is_empty());
+	group.bench_function("spin", |b| b.iter(|| vm.step(CYCLES, &mut (), &mut (), &mut ())));
+	group.finish();
+}
+
+fn boot(c: &mut Criterion) {
+	let mut group = c.benchmark_group("boot");
+	bench(&mut group, "bye", b"0 (bye)\n");
+	group.finish();
+}
+
+fn dictionary(c: &mut Criterion) {
+	let mut group = c.benchmark_group("dictionary");
+	group.throughput(Throughput::Elements(REPEAT));
+	let find = format!(": s $\" rot\" ; : f {} for s find drop drop next ; f 0 (bye)\n", REPEAT - 1);
+	bench(&mut group, "find", find.as_bytes());
+	let missing = format!(": s $\" no-such-word\" ; : f {} for s find drop drop next ; f 0 (bye)\n", REPEAT - 1);
+	bench(&mut group, "find-missing", missing.as_bytes());
+	group.throughput(Throughput::Elements(1));
+	bench(&mut group, "words", b"words 0 (bye)\n");
+	group.finish();
+}
+
+fn format(c: &mut Criterion) {
+	let mut group = c.benchmark_group("format");
+	group.throughput(Throughput::Elements(REPEAT));
+	let decimal = format!("decimal : n {} for r@ 31 * . next ; n 0 (bye)\n", REPEAT - 1);
+	bench(&mut group, "decimal", decimal.as_bytes());
+	let hex = format!("hex : n {:X} for r@ 31 * u. next ; n 0 (bye)\n", REPEAT - 1);
+	bench(&mut group, "hex", hex.as_bytes());
+	group.finish();
+}
+
+fn compile(c: &mut Criterion) {
+	let mut group = c.benchmark_group("compile");
+	group.throughput(Throughput::Elements(DEFINITIONS));
+	let mut source = String::from("decimal\n");
+	for i in 0..DEFINITIONS {
+		source += &format!(": w{} dup 1+ swap over + drop {} if {} . then ;\n", i, i % 3, i);
+	}
+	source += "w1 0 (bye)\n";
+	bench(&mut group, "definitions", source.as_bytes());
+	group.finish();
+}
+
+criterion_group!(benches, throughput, boot, dictionary, format, compile);
+criterion_main!(benches);
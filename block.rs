@@ -0,0 +1,179 @@
+//! # Block Storage
+//!
+//! Forth keeps source and data in blocks of 1024 characters, numbered from
+//! zero, which the virtual machine reads and writes with two ALU operations,
+//! 28 to read a block into a buffer in memory and 29 to write a buffer out
+//! to a block. Both take `( a-addr u -- ior )`, the byte address of the
+//! buffer, which takes up 512 cells, and the block number, giving zero on
+//! success and all bits set on an error. The blocks are kept by the `Store`
+//! the virtual machine is run with, `Blocks` keeps them in memory and
+//! `BlockFile` in a file. The two operations are not in the instruction set
+//! of the C virtual machine or the hardware, so an image that uses them
+//! only runs on this one.
+//!
+//! `WORDS` is Forth source for the eForth image that defines the block word
+//! set, `BLOCK`, `BUFFER`, `UPDATE`, `FLUSH`, `LOAD` and others, on top of
+//! these operations, with a single buffer.
+
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::path::Path;
+
+use host::{Store, BLOCK_SIZE};
+use image::ByteOrder;
+
+/// `WORDS` defines the block words in the eForth image, it should be given
+/// to the image as input before any blocks are used.
+pub const WORDS: &str = include_str!("block.fth");
+
+/// `Blocks` is a fixed number of blocks held in memory, which start out
+/// filled with spaces. It does not save memory with the save instruction.
+pub struct Blocks(Vec<[u8; BLOCK_SIZE]>);
+
+impl Blocks {
+	/// `new` makes `count` blank blocks.
+	pub fn new(count: u16) -> Self {
+		Blocks(vec![[b' '; BLOCK_SIZE]; count as usize])
+	}
+
+	/// `blocks` gives access to the contents of the blocks, for filling them
+	/// in before running the virtual machine or examining them afterwards.
+	pub fn blocks(&mut self) -> &mut [[u8; BLOCK_SIZE]] {
+		&mut self.0
+	}
+}
+
+impl Store for Blocks {
+	fn store(&mut self, _cells: &[u16], _order: ByteOrder) -> bool { false }
+
+	fn read_block(&mut self, block: u16, buffer: &mut [u8; BLOCK_SIZE]) -> bool {
+		match self.0.get(block as usize) {
+			Some(b) => { buffer.copy_from_slice(b); true }
+			None => false,
+		}
+	}
+
+	fn write_block(&mut self, block: u16, buffer: &[u8; BLOCK_SIZE]) -> bool {
+		match self.0.get_mut(block as usize) {
+			Some(b) => { b.copy_from_slice(buffer); true }
+			None => false,
+		}
+	}
+}
+
+/// `BlockFile` keeps blocks in a file, block `u` being the 1024 bytes at
+/// `u * 1024`. Blocks past the end of the file read as spaces, and writing
+/// one extends the file. It does not save memory with the save instruction.
+pub struct BlockFile(File);
+
+impl BlockFile {
+	/// `open` opens the block file at `path` for reading and writing,
+	/// creating it if it does not exist.
+	pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+		OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path).map(BlockFile)
+	}
+}
+
+impl Store for BlockFile {
+	fn store(&mut self, _cells: &[u16], _order: ByteOrder) -> bool { false }
+
+	fn read_block(&mut self, block: u16, buffer: &mut [u8; BLOCK_SIZE]) -> bool {
+		if self.0.seek(SeekFrom::Start(block as u64 * BLOCK_SIZE as u64)).is_err() { return false }
+		let mut read = 0;
+		while read < BLOCK_SIZE {
+			match self.0.read(&mut buffer[read..]) {
+				Ok(0) => break,
+				Ok(n) => read += n,
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => { }
+				Err(_) => return false,
+			}
+		}
+		buffer[read..].fill(b' ');
+		true
+	}
+
+	fn write_block(&mut self, block: u16, buffer: &[u8; BLOCK_SIZE]) -> bool {
+		self.0.seek(SeekFrom::Start(block as u64 * BLOCK_SIZE as u64)).is_ok()
+			&& self.0.write_all(buffer).is_ok()
+			&& self.0.flush().is_ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use {Registers, VM, RP0, SP0};
+
+	/// `transfer` runs the block operation `op` on the buffer at cell
+	/// `0x4000` and `block`, returning the result.
+	fn transfer(vm: &mut VM, store: &mut dyn Store, op: u16, block: u16) -> u16 {
+		vm.core[SP0 as usize] = 0x8000;
+		vm.core[0x100] = 0x6000 | op << 8 | 0x03;
+		vm.set_registers(Registers { pc: 0x100, rp: RP0, sp: SP0, t: block });
+		vm.step(1, store, &mut (), &mut ());
+		vm.t
+	}
+
+	#[test]
+	fn memory() {
+		let mut vm = VM::blank();
+		let mut blocks = Blocks::new(2);
+		blocks.blocks()[1][..2].copy_from_slice(b"hi");
+		assert_eq!(transfer(&mut vm, &mut blocks, 28, 1), 0);
+		assert_eq!(vm.read(0x4000), 0x6968);
+		assert_eq!(vm.read(0x4001), 0x2020);
+		vm.write(0x41ff, 0x2121);
+		assert_eq!(transfer(&mut vm, &mut blocks, 29, 0), 0);
+		assert_eq!(&blocks.blocks()[0][..2], b"hi");
+		assert_eq!(&blocks.blocks()[0][BLOCK_SIZE - 2..], b"!!");
+		assert_eq!(transfer(&mut vm, &mut blocks, 28, 2), 0xffff);
+		assert_eq!(transfer(&mut vm, &mut blocks, 29, 2), 0xffff);
+	}
+
+	#[test]
+	fn file() {
+		let path = std::env::temp_dir().join(format!("embed-blocks-{}.blk", std::process::id()));
+		let _ignore = std::fs::remove_file(&path);
+		let mut file = BlockFile::open(&path).unwrap();
+		let mut buffer = [0; BLOCK_SIZE];
+		assert!(file.read_block(3, &mut buffer));
+		assert!(buffer.iter().all(|&c| c == b' '));
+
+		buffer[0] = b'a';
+		assert!(file.write_block(2, &buffer));
+		assert_eq!(std::fs::metadata(&path).unwrap().len(), 3 * BLOCK_SIZE as u64);
+		let mut file = BlockFile::open(&path).unwrap();
+		let mut read = [0; BLOCK_SIZE];
+		assert!(file.read_block(2, &mut read));
+		assert!(read[..] == buffer[..]);
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	/// `eforth` runs `source` on the eForth image, after the block words and
+	/// without prompts, returning what it printed.
+	#[cfg(feature = "eforth")]
+	fn eforth(blocks: &mut Blocks, source: &str) -> String {
+		let input = format!(": (quiet) ; ' (quiet) <ok> !\n{}{}", WORDS, source);
+		let mut output = Vec::new();
+		assert_eq!(VM::new().execute(blocks, &mut input.as_bytes(), &mut output), 0);
+		String::from_utf8_lossy(&output).into_owned()
+	}
+
+	#[test]
+	#[cfg(feature = "eforth")]
+	fn words() {
+		let mut blocks = Blocks::new(4);
+		blocks.blocks()[1][..12].copy_from_slice(b": sq dup * ;");
+		blocks.blocks()[1][64..71].copy_from_slice(b"7 sq . ");
+		blocks.blocks()[2][..6].copy_from_slice(b"3 sq .");
+		let output = eforth(&mut blocks, "1 2 thru\n3 buffer 41 over c! 42 swap 1+ c! update flush\n0 (bye)\n");
+		assert!(output.ends_with("list redefined \r\n 31 9"), "{}", output);
+		assert_eq!(&blocks.blocks()[3][..2], b"AB");
+
+		let output = eforth(&mut blocks, "1 list\n9 block\n0 (bye)\n");
+		assert!(output.contains(" 0 : sq dup * ;"), "{}", output);
+		assert!(output.contains(" 1 7 sq ."), "{}", output);
+		assert!(output.ends_with(" FFDF?\r\n"), "{}", output);
+	}
+}
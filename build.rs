@@ -0,0 +1,92 @@
+//! Generates the images bundled with the library, each image enabled by a
+//! cargo feature is read from its default file, or the file named by its
+//! environment variable, so a newer image from <https://github.com/howerj/embed>
+//! can be used by copying it over `eforth.blk`. Images are validated in the
+//! same way as `VM::load` would, an invalid image fails the build.
+
+extern crate core;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+#[path = "image.rs"]
+#[allow(dead_code)]
+mod image;
+#[path = "translate.rs"]
+#[allow(dead_code)]
+mod translate;
+
+/// `IMAGES` lists the cargo feature, generated constant, default file and
+/// environment variable for each image.
+const IMAGES: [(&str, &str, &str, &str); 3] = [
+	("EFORTH", "EFORTH_CORE", "eforth.blk", "EFORTH_IMAGE"),
+	("KERNEL", "KERNEL_CORE", "kernel.mem", "KERNEL_IMAGE"),
+	("TEST_IMAGE", "TEST_CORE", "test.mem", "TEST_IMAGE"),
+];
+
+fn main() {
+	let out = Path::new(&env::var("OUT_DIR").unwrap()).join("images.rs");
+	let mut file = fs::File::create(out).unwrap();
+	for &(feature, name, default, var) in &IMAGES {
+		println!("cargo:rerun-if-env-changed={}", var);
+		if env::var_os(format!("CARGO_FEATURE_{}", feature)).is_none() { continue }
+		let path = env::var(var).unwrap_or_else(|_| default.to_string());
+		println!("cargo:rerun-if-changed={}", path);
+		let cells = read(&path);
+
+		writeln!(file, "/// `{}` has been generated from `{}`", name, path).unwrap();
+		writeln!(file, "pub const {}: [u16; {}] = [", name, cells.len()).unwrap();
+		for line in cells.chunks(16) {
+			let line: Vec<String> = line.iter().map(|c| format!("0x{:04x}", c)).collect();
+			writeln!(file, "\t{},", line.join(", ")).unwrap();
+		}
+		writeln!(file, "];").unwrap();
+
+		if feature == "EFORTH" { /* the bundled eForth is also translated, see `aot.rs` */
+			let aot = Path::new(&env::var("OUT_DIR").unwrap()).join("eforth_aot.rs");
+			fs::write(aot, translate::translate(&cells, "aot")).unwrap();
+		}
+	}
+}
+
+/// `read` loads and validates an image, Verilog memory files are accepted as
+/// well as raw images.
+fn read(path: &str) -> Vec<u16> {
+	let bytes = fs::read(path).unwrap_or_else(|e| panic!("could not read image \"{}\": {}", path, e));
+	let cells = if path.ends_with(".mem") {
+		verilog(&String::from_utf8_lossy(&bytes)).unwrap_or_else(|| panic!("invalid Verilog memory file \"{}\"", path))
+	} else {
+		if !bytes.len().is_multiple_of(2) || bytes.len() > 0x10000 {
+			panic!("image \"{}\" has an invalid length of {} bytes", path, bytes.len());
+		}
+		let order = image::ByteOrder::detect(&bytes).unwrap_or_default();
+		bytes.chunks_exact(2).map(|b| order.cell(b)).collect()
+	};
+	if let Err(e) = image::ImageHeader::parse(&cells).and_then(|h| h.check_size(cells.len() * 2)) {
+		panic!("invalid image \"{}\": {}", path, e);
+	}
+	cells
+}
+
+/// `verilog` reads a Verilog memory file, this is a cut down version of the
+/// reader in `format.rs` which needs the standard library features of the
+/// crate, which build scripts do not get.
+fn verilog(text: &str) -> Option<Vec<u16>> {
+	let mut cells = Vec::new();
+	let mut address = 0;
+	for line in text.lines() {
+		for word in line.split("//").next().unwrap_or("").split_whitespace() {
+			if let Some(a) = word.strip_prefix('@') {
+				address = usize::from_str_radix(a, 16).ok()?;
+				continue;
+			}
+			if address >= 0x8000 { return None }
+			if cells.len() <= address { cells.resize(address + 1, 0) }
+			cells[address] = u16::from_str_radix(word, 16).ok()?;
+			address += 1;
+		}
+	}
+	Some(cells)
+}
@@ -0,0 +1,323 @@
+//! # C API for the Embed Virtual Machine
+//!
+//! This library exposes the virtual machine to C, it is intended to be a
+//! replacement for the C version of the virtual machine available at
+//! <https://github.com/howerj/embed>. The header `embed.h` describes the
+//! interface, it can be regenerated with "cbindgen --config cbindgen.toml
+//! --output embed.h" from this directory.
+//!
+//! All functions accept a null handle and return an error, other pointers
+//! must be valid for the duration of the call. Input and output default to
+//! the standard streams and saving to being disallowed, until callbacks are
+//! registered.
+
+#![allow(non_camel_case_types)]
+
+extern crate embed;
+
+use std::io::prelude::*;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::slice;
+
+use embed::{ByteOrder, Input, Limits, Output, Outcome, Store, VM};
+
+/// `EMBED_RUNNING` is returned by `embed_step` when it has executed as many
+/// instructions as it was allowed to and the virtual machine is still running.
+pub const EMBED_RUNNING: c_int = 0;
+/// `EMBED_HALTED` is returned by `embed_step` and `embed_run_limited` when
+/// the virtual machine halted.
+pub const EMBED_HALTED: c_int = 1;
+/// `EMBED_NEED_INPUT` is returned by `embed_step` and `embed_run_limited`
+/// when the virtual machine stopped before reading input, as the `available`
+/// callback said there was none, it carries on from there when run again.
+pub const EMBED_NEED_INPUT: c_int = 2;
+/// `EMBED_TIMEOUT` is returned by `embed_run_limited` when it has executed as
+/// many instructions as it was allowed to without the virtual machine halting
+/// or waiting for input.
+pub const EMBED_TIMEOUT: c_int = 3;
+
+/// `embed_getc_t` returns the next character of input, or a negative number
+/// on end of input or error.
+pub type embed_getc_t = Option<unsafe extern "C" fn(param: *mut c_void) -> c_int>;
+
+/// `embed_available_t` returns non-zero if a character can be read without
+/// waiting for it.
+pub type embed_available_t = Option<unsafe extern "C" fn(param: *mut c_void) -> c_int>;
+
+/// `embed_putc_t` writes a single character, returning it on success or a
+/// negative number on error.
+pub type embed_putc_t = Option<unsafe extern "C" fn(ch: c_int, param: *mut c_void) -> c_int>;
+
+/// `embed_save_t` is given the section of memory written by the save
+/// instruction, already in the byte order of the virtual machine, it should
+/// return zero on success.
+pub type embed_save_t = Option<unsafe extern "C" fn(bytes: *const u8, length: usize, param: *mut c_void) -> c_int>;
+
+/// `embed_t` is an opaque handle to a virtual machine instance.
+pub struct embed_t {
+	vm: VM,
+	getc: Getc,
+	putc: Putc,
+	save: Save,
+}
+
+/// `Getc`, `Putc` and `Save` adapt the registered callbacks, along with the
+/// parameter passed to each, to the traits the virtual machine uses for I/O.
+/// `Getc` also asks the `available` callback, if there is one, whether there
+/// is any input.
+struct Getc(embed_getc_t, *mut c_void, embed_available_t, *mut c_void);
+struct Putc(embed_putc_t, *mut c_void);
+struct Save(embed_save_t, *mut c_void);
+
+impl Input for Getc {
+	fn getc(&mut self) -> Option<u8> {
+		match self.0 {
+			Some(f) => { let c = unsafe { f(self.1) }; if c < 0 { None } else { Some(c as u8) } }
+			None => {
+				let mut u: [u8; 1] = [0];
+				if 1 == std::io::stdin().read(&mut u).unwrap_or(0) { Some(u[0]) } else { None }
+			}
+		}
+	}
+
+	fn available(&mut self) -> bool {
+		match self.2 {
+			Some(f) => unsafe { f(self.3) != 0 },
+			None => true,
+		}
+	}
+}
+
+impl Output for Putc {
+	fn putc(&mut self, c: u8) -> bool {
+		match self.0 {
+			Some(f) => unsafe { f(c as c_int, self.1) >= 0 },
+			None => {
+				let mut out = std::io::stdout();
+				out.write_all(&[c]).and_then(|_| if c == b'\n' { out.flush() } else { Ok(()) }).is_ok()
+			}
+		}
+	}
+}
+
+impl Store for Save {
+	fn store(&mut self, cells: &[u16], order: ByteOrder) -> bool {
+		match self.0 {
+			Some(f) => {
+				let bytes: Vec<u8> = cells.iter().flat_map(|&c| order.bytes(c)).collect();
+				unsafe { f(bytes.as_ptr(), bytes.len(), self.1) == 0 }
+			}
+			None => false,
+		}
+	}
+}
+
+impl embed_t {
+	fn step(&mut self, cycles: u64) -> Outcome {
+		self.vm.step(cycles, &mut self.save, &mut self.getc, &mut self.putc)
+	}
+
+	fn run_limited(&mut self, cycles: u64) -> Outcome {
+		let limits = Limits { cycles: Some(cycles), ..Default::default() };
+		self.vm.run_limited(&limits, &mut self.save, &mut self.getc, &mut self.putc)
+	}
+}
+
+/// `status` turns `outcome` into the value returned to C, storing the value
+/// the virtual machine halted with in `code` if it is not null.
+unsafe fn status(outcome: Outcome, code: *mut c_int) -> c_int {
+	match outcome {
+		Outcome::Halted(r) => { if !code.is_null() { *code = r } EMBED_HALTED }
+		Outcome::Running => EMBED_RUNNING,
+		Outcome::NeedInput => EMBED_NEED_INPUT,
+		Outcome::Timeout => EMBED_TIMEOUT,
+	}
+}
+
+/// `embed_new` creates a new virtual machine containing the eForth image.
+///
+/// # Returns
+///
+/// A handle to be freed with `embed_free`, or null on failure.
+#[no_mangle]
+pub extern "C" fn embed_new() -> *mut embed_t {
+	let h = Box::new(embed_t {
+		vm: VM::new(),
+		getc: Getc(None, ptr::null_mut(), None, ptr::null_mut()),
+		putc: Putc(None, ptr::null_mut()),
+		save: Save(None, ptr::null_mut()),
+	});
+	Box::into_raw(h)
+}
+
+/// `embed_free` releases a virtual machine created with `embed_new`.
+///
+/// # Safety
+///
+/// `h` must have been returned by `embed_new` and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn embed_free(h: *mut embed_t) {
+	if !h.is_null() { drop(Box::from_raw(h)) }
+}
+
+/// `embed_load` replaces the image in the virtual machine with one held in
+/// memory, the image is validated first and the registers are reset.
+///
+/// # Returns
+///
+/// Zero on success, negative on failure, in which case the virtual machine
+/// is left unchanged.
+///
+/// # Safety
+///
+/// `image` must point to `length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn embed_load(h: *mut embed_t, image: *const u8, length: usize) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	if image.is_null() { return -1 }
+	match VM::from_bytes(slice::from_raw_parts(image, length)) {
+		Ok(vm) => { h.vm = vm; 0 }
+		Err(_) => -1,
+	}
+}
+
+/// `embed_run` runs the virtual machine until it halts.
+///
+/// # Returns
+///
+/// The value the program running on the virtual machine halted with.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn embed_run(h: *mut embed_t) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	loop {
+		if let Outcome::Halted(r) = h.step(u64::MAX) { return r }
+	}
+}
+
+/// `embed_step` runs the virtual machine for at most `cycles` instructions.
+///
+/// # Returns
+///
+/// `EMBED_HALTED` if the virtual machine halted, with the value it halted
+/// with stored in `code` if it is not null, `EMBED_RUNNING` if it is still
+/// running, `EMBED_NEED_INPUT` if it is waiting for input, and negative on
+/// error.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `code` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn embed_step(h: *mut embed_t, cycles: u64, code: *mut c_int) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	status(h.step(cycles), code)
+}
+
+/// `embed_run_limited` runs the virtual machine until it halts or waits for
+/// input, as `embed_run` does, but for at most `cycles` instructions, so a
+/// program that does neither cannot run forever. Calling it again carries
+/// on where it stopped.
+///
+/// # Returns
+///
+/// `EMBED_HALTED` if the virtual machine halted, with the value it halted
+/// with stored in `code` if it is not null, `EMBED_NEED_INPUT` if it is
+/// waiting for input, `EMBED_TIMEOUT` if it ran for all `cycles`, and
+/// negative on error.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `code` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn embed_run_limited(h: *mut embed_t, cycles: u64, code: *mut c_int) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	status(h.run_limited(cycles), code)
+}
+
+/// `embed_push` pushes a value onto the variable stack.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn embed_push(h: *mut embed_t, value: u16) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	h.vm.push(value);
+	0
+}
+
+/// `embed_pop` pops a value from the variable stack into `value`.
+///
+/// # Returns
+///
+/// Zero on success, negative if the stack is empty.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `value` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn embed_pop(h: *mut embed_t, value: *mut u16) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	match h.vm.pop() {
+		Some(v) => { if !value.is_null() { *value = v } 0 }
+		None => -1,
+	}
+}
+
+/// `embed_set_getc` registers the input callback, null restores the default
+/// of reading from standard input.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `param` is passed to `getc` as is.
+#[no_mangle]
+pub unsafe extern "C" fn embed_set_getc(h: *mut embed_t, getc: embed_getc_t, param: *mut c_void) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	h.getc = Getc(getc, param, h.getc.2, h.getc.3);
+	0
+}
+
+/// `embed_set_available` registers a callback that says whether there is any
+/// input, so that `embed_step` returns `EMBED_NEED_INPUT` instead of waiting
+/// in `getc`. Null restores the default of input always being available.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `param` is passed to `available` as is.
+#[no_mangle]
+pub unsafe extern "C" fn embed_set_available(h: *mut embed_t, available: embed_available_t, param: *mut c_void) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	h.getc.2 = available;
+	h.getc.3 = param;
+	0
+}
+
+/// `embed_set_putc` registers the output callback, null restores the
+/// default of writing to standard output.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `param` is passed to `putc` as is.
+#[no_mangle]
+pub unsafe extern "C" fn embed_set_putc(h: *mut embed_t, putc: embed_putc_t, param: *mut c_void) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	h.putc = Putc(putc, param);
+	0
+}
+
+/// `embed_set_save` registers the callback for the save instruction, null
+/// restores the default of refusing to save.
+///
+/// # Safety
+///
+/// `h` must be null or a valid handle, `param` is passed to `save` as is.
+#[no_mangle]
+pub unsafe extern "C" fn embed_set_save(h: *mut embed_t, save: embed_save_t, param: *mut c_void) -> c_int {
+	let h = match h.as_mut() { Some(h) => h, None => return -1 };
+	h.save = Save(save, param);
+	0
+}
@@ -0,0 +1,173 @@
+#ifndef EMBED_H
+#define EMBED_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// `EMBED_RUNNING` is returned by `embed_step` when it has executed as many
+// instructions as it was allowed to and the virtual machine is still running.
+#define EMBED_RUNNING 0
+
+// `EMBED_HALTED` is returned by `embed_step` and `embed_run_limited` when
+// the virtual machine halted.
+#define EMBED_HALTED 1
+
+// `EMBED_NEED_INPUT` is returned by `embed_step` and `embed_run_limited`
+// when the virtual machine stopped before reading input, as the `available`
+// callback said there was none, it carries on from there when run again.
+#define EMBED_NEED_INPUT 2
+
+// `EMBED_TIMEOUT` is returned by `embed_run_limited` when it has executed as
+// many instructions as it was allowed to without the virtual machine halting
+// or waiting for input.
+#define EMBED_TIMEOUT 3
+
+// `embed_t` is an opaque handle to a virtual machine instance.
+typedef struct embed_t embed_t;
+
+// `embed_getc_t` returns the next character of input, or a negative number
+// on end of input or error.
+typedef int (*embed_getc_t)(void *param);
+
+// `embed_available_t` returns non-zero if a character can be read without
+// waiting for it.
+typedef int (*embed_available_t)(void *param);
+
+// `embed_putc_t` writes a single character, returning it on success or a
+// negative number on error.
+typedef int (*embed_putc_t)(int ch, void *param);
+
+// `embed_save_t` is given the section of memory written by the save
+// instruction, already in the byte order of the virtual machine, it should
+// return zero on success.
+typedef int (*embed_save_t)(const uint8_t *bytes, size_t length, void *param);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// `embed_new` creates a new virtual machine containing the eForth image.
+//
+// # Returns
+//
+// A handle to be freed with `embed_free`, or null on failure.
+embed_t *embed_new(void);
+
+// `embed_free` releases a virtual machine created with `embed_new`.
+//
+// # Safety
+//
+// `h` must have been returned by `embed_new` and not already freed.
+void embed_free(embed_t *h);
+
+// `embed_load` replaces the image in the virtual machine with one held in
+// memory, the image is validated first and the registers are reset.
+//
+// # Returns
+//
+// Zero on success, negative on failure, in which case the virtual machine
+// is left unchanged.
+//
+// # Safety
+//
+// `image` must point to `length` readable bytes.
+int embed_load(embed_t *h, const uint8_t *image, size_t length);
+
+// `embed_run` runs the virtual machine until it halts.
+//
+// # Returns
+//
+// The value the program running on the virtual machine halted with.
+//
+// # Safety
+//
+// `h` must be null or a valid handle.
+int embed_run(embed_t *h);
+
+// `embed_step` runs the virtual machine for at most `cycles` instructions.
+//
+// # Returns
+//
+// `EMBED_HALTED` if the virtual machine halted, with the value it halted
+// with stored in `code` if it is not null, `EMBED_RUNNING` if it is still
+// running, `EMBED_NEED_INPUT` if it is waiting for input, and negative on
+// error.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `code` null or writable.
+int embed_step(embed_t *h, uint64_t cycles, int *code);
+
+// `embed_run_limited` runs the virtual machine until it halts or waits for
+// input, as `embed_run` does, but for at most `cycles` instructions, so a
+// program that does neither cannot run forever. Calling it again carries
+// on where it stopped.
+//
+// # Returns
+//
+// `EMBED_HALTED` if the virtual machine halted, with the value it halted
+// with stored in `code` if it is not null, `EMBED_NEED_INPUT` if it is
+// waiting for input, `EMBED_TIMEOUT` if it ran for all `cycles`, and
+// negative on error.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `code` null or writable.
+int embed_run_limited(embed_t *h, uint64_t cycles, int *code);
+
+// `embed_push` pushes a value onto the variable stack.
+//
+// # Safety
+//
+// `h` must be null or a valid handle.
+int embed_push(embed_t *h, uint16_t value);
+
+// `embed_pop` pops a value from the variable stack into `value`.
+//
+// # Returns
+//
+// Zero on success, negative if the stack is empty.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `value` null or writable.
+int embed_pop(embed_t *h, uint16_t *value);
+
+// `embed_set_getc` registers the input callback, null restores the default
+// of reading from standard input.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `param` is passed to `getc` as is.
+int embed_set_getc(embed_t *h, embed_getc_t getc, void *param);
+
+// `embed_set_available` registers a callback that says whether there is any
+// input, so that `embed_step` returns `EMBED_NEED_INPUT` instead of waiting
+// in `getc`. Null restores the default of input always being available.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `param` is passed to `available` as is.
+int embed_set_available(embed_t *h, embed_available_t available, void *param);
+
+// `embed_set_putc` registers the output callback, null restores the
+// default of writing to standard output.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `param` is passed to `putc` as is.
+int embed_set_putc(embed_t *h, embed_putc_t putc, void *param);
+
+// `embed_set_save` registers the callback for the save instruction, null
+// restores the default of refusing to save.
+//
+// # Safety
+//
+// `h` must be null or a valid handle, `param` is passed to `save` as is.
+int embed_set_save(embed_t *h, embed_save_t save, void *param);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  // EMBED_H
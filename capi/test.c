@@ -0,0 +1,87 @@
+/* Test program for the C API, it is compiled and run by "tests/c.rs", and
+ * exits with zero on success. */
+#include "embed.h"
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+	const char *in;
+	char out[4096];
+	size_t used;
+	size_t saved;
+} buffers_t;
+
+static int get(void *param) {
+	buffers_t *b = param;
+	return *b->in ? *b->in++ : -1;
+}
+
+static int ready(void *param) {
+	buffers_t *b = param;
+	return *b->in != 0;
+}
+
+static int put(int ch, void *param) {
+	buffers_t *b = param;
+	if (b->used >= sizeof(b->out) - 1)
+		return -1;
+	b->out[b->used++] = ch;
+	return ch;
+}
+
+static int save(const uint8_t *bytes, size_t length, void *param) {
+	buffers_t *b = param;
+	(void)bytes;
+	b->saved = length;
+	return 0;
+}
+
+#define TEST(EXPR) do { if (!(EXPR)) { fprintf(stderr, "%s:%d: test failed: %s\n", __FILE__, __LINE__, #EXPR); return 1; } } while (0)
+
+int main(void) {
+	buffers_t b = { .in = "decimal 2 3 + . 0 100 (save) drop 42 (bye)\n" };
+	uint16_t value = 0;
+	int code = 0;
+	embed_t *h = embed_new();
+	TEST(h);
+	TEST(embed_set_getc(h, get, &b) == 0);
+	TEST(embed_set_putc(h, put, &b) == 0);
+	TEST(embed_set_save(h, save, &b) == 0);
+
+	TEST(embed_push(h, 7) == 0);
+	TEST(embed_pop(h, &value) == 0 && value == 7);
+	TEST(embed_pop(h, &value) < 0);
+
+	TEST(embed_step(h, 10, &code) == EMBED_RUNNING);
+	TEST(embed_run(h) == 42);
+	TEST(strstr(b.out, " 5") != NULL);
+	TEST(b.saved == 100);
+	embed_free(h);
+
+	buffers_t w = { .in = "" };
+	h = embed_new();
+	TEST(h);
+	TEST(embed_set_getc(h, get, &w) == 0);
+	TEST(embed_set_available(h, ready, &w) == 0);
+	TEST(embed_set_putc(h, put, &w) == 0);
+	TEST(embed_step(h, 1000000, &code) == EMBED_NEED_INPUT);
+	w.in = "7 (bye)\n";
+	TEST(embed_step(h, 1000000, &code) == EMBED_HALTED && code == 7);
+	embed_free(h);
+
+	buffers_t l = { .in = ": x begin again ; x\n" };
+	h = embed_new();
+	TEST(h);
+	TEST(embed_set_getc(h, get, &l) == 0);
+	TEST(embed_set_available(h, ready, &l) == 0);
+	TEST(embed_set_putc(h, put, &l) == 0);
+	TEST(embed_run_limited(h, 1000000, &code) == EMBED_TIMEOUT);
+	TEST(embed_run_limited(h, 1000000, &code) == EMBED_TIMEOUT);
+	TEST(embed_run_limited(NULL, 1000000, &code) < 0);
+
+	TEST(embed_load(h, (const uint8_t*)"garbage", 7) < 0);
+	TEST(embed_load(NULL, NULL, 0) < 0);
+	TEST(embed_run(NULL) < 0);
+	embed_free(h);
+	return 0;
+}
@@ -0,0 +1,30 @@
+//! Compiles `test.c` against `embed.h` and the static library, then runs it.
+//! The test is skipped if there is no C compiler, which can be set with the
+//! `CC` environment variable.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_api() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+	let exe = env::current_exe().unwrap();
+	let lib = exe.parent().and_then(|d| d.parent()).unwrap().join("libembed_capi.a");
+	let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("embed-capi-test");
+	let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+	let compiled = Command::new(&cc)
+		.arg("-std=c99").arg("-Wall").arg("-Wextra")
+		.arg("-I").arg(&dir)
+		.arg(dir.join("test.c")).arg(&lib)
+		.args(["-lpthread", "-ldl", "-lm"])
+		.arg("-o").arg(&out)
+		.status();
+	let compiled = match compiled {
+		Err(e) => { eprintln!("skipping C API test, could not run \"{}\": {}", cc, e); return }
+		Ok(status) => status,
+	};
+	assert!(compiled.success(), "failed to compile test.c");
+	assert!(Command::new(&out).status().unwrap().success(), "test.c failed");
+}
@@ -0,0 +1,297 @@
+//! # Conformance Tests
+//!
+//! Table driven tests of every instruction, each executed on its own by
+//! `VM::step` from a known state, with input, output and saving to memory
+//! instead of the terminal or files. The ALU operations are each given one
+//! or more rows of what they do to the top and next of stack, and are then
+//! run with every combination of the R->PC, N->T, T->R and T->N flags and
+//! stack deltas, checking all of the registers and memory afterwards.
+
+use super::*;
+
+/// `PC` is where the instruction under test is placed.
+const PC: u16 = 0x100;
+/// `RP` is the return stack pointer to start with, `RETURN` is the byte
+/// address on the top of the return stack.
+const RP: u16 = 0x7ff0;
+const RETURN: u16 = 0x400;
+/// `BELOW` is the item on the variable stack below the next on stack.
+const BELOW: u16 = 0x1111;
+/// `CELL` is a cell loaded from and stored to, `BEEF` its contents.
+const CELL: u16 = 0x300;
+const BEEF: u16 = 0xbeef;
+/// `INPUT` is the character available to read.
+const INPUT: u8 = b'Z';
+
+/// `Saved` records what the save instruction stores.
+struct Saved(Vec<u16>);
+
+impl Store for Saved {
+	fn store(&mut self, cells: &[u16], _order: ByteOrder) -> bool { self.0.extend_from_slice(cells); true }
+}
+
+/// `Broken` is output that always fails.
+struct Broken;
+
+impl Output for Broken {
+	fn putc(&mut self, _c: u8) -> bool { false }
+}
+
+/// `Row` is what an ALU operation does, given `t` and `n`.
+struct Row {
+	op: u16,
+	t: u16,
+	n: u16,
+	/// `top` is the new top of stack, unless N->T is set
+	top: u16,
+	/// `tn` are the top and next of stack used by the flags, which only the
+	/// division operations change
+	tn: (u16, u16),
+	/// `sp` and `rp` are the stack pointers before the deltas are applied
+	sp: u16,
+	rp: u16,
+	/// `trap` is set if the operation jumps to the trap handler
+	trap: bool,
+	/// `write` is a cell written by the operation itself
+	write: Option<(u16, u16)>,
+}
+
+/// `row` makes a `Row` for an operation that only sets the top of stack.
+fn row(op: u16, t: u16, n: u16, top: u16) -> Row {
+	Row { op, t, n, top, tn: (t, n), sp: SP0, rp: RP, trap: false, write: None }
+}
+
+fn rows() -> Vec<Row> {
+	vec![
+		row(0, 0x1234, 0x5678, 0x1234),
+		row(1, 0x1234, 0x5678, 0x5678),
+		row(2, 0x1234, 0x5678, RETURN),
+		row(3, CELL << 1, 0x5678, BEEF),
+		Row { sp: SP0 - 1, write: Some((CELL, 0x5678)), ..row(4, CELL << 1, 0x5678, BELOW) },
+		Row { tn: (0xfff0, 0x0010), write: Some((SP0, 0x0010)), ..row(5, 0xfff0, 0x0020, 1) },
+		Row { tn: (0x1234, 0x3400), write: Some((SP0, 0x3400)), ..row(6, 0x1234, 0x0100, 0x0012) },
+		row(7, 0x1234, 0x5678, 0x1230),
+		row(8, 0x1234, 0x5678, 0x567c),
+		row(9, 0x1234, 0x5678, 0x444c),
+		row(10, 0x1234, 0x5678, 0xedcb),
+		row(11, 0x1234, 0x5678, 0x1233),
+		row(11, 0, 0x5678, 0xffff),
+		row(12, 0, 0x5678, 0xffff),
+		row(12, 5, 0x5678, 0),
+		row(13, 7, 7, 0xffff),
+		row(13, 7, 8, 0),
+		row(14, 0xffff, 1, 0xffff),
+		row(14, 1, 0xffff, 0),
+		row(15, 1, 0xffff, 0xffff),
+		row(15, 0xffff, 1, 0),
+		row(16, 4, 0x8000, 0x0800),
+		row(16, 16, 0x8000, 0),
+		row(17, 15, 1, 0x8000),
+		row(17, 16, 1, 0),
+		row(18, 0x1234, 0x5678, SP0 << 1),
+		row(19, 0x1234, 0x5678, RP << 1),
+		Row { sp: 0x1000, ..row(20, 0x2000, 0x5678, 0x2000) },
+		Row { rp: 0x3000, ..row(21, 0x6000, 0x5678, 0x5678) },
+		row(22, 7, 0, 0),
+		row(23, 0x141, 0x5678, 0x41),
+		row(24, 0x1234, 0x5678, INPUT as u16),
+		Row { tn: (1, 1), ..row(25, 2, 7, 3) },
+		Row { tn: (0xfffe, 0xfffe), ..row(25, 0xffff, 0xfffe, 0) },
+		Row { trap: true, ..row(25, 0, 7, 10) },
+		Row { tn: (0xffff, 0xffff), ..row(26, 2, 0xfff9, 0xfffd) },
+		Row { tn: (0, 0), ..row(26, 0xffff, 0x8000, 0x8000) },
+		Row { trap: true, ..row(26, 0, 7, 10) },
+		row(28, 0x1234, 0x5678, 0xffff),
+		row(29, 0x1234, 0x5678, 0xffff),
+		row(30, 0x1234, 0x5678, 0x1234),
+		row(31, 0x1234, 0x5678, 0x1234),
+	]
+}
+
+/// `start` makes a virtual machine about to execute `instruction`, with `t`
+/// and `n` on the variable stack.
+fn start(instruction: u16, t: u16, n: u16) -> VM {
+	let mut vm = VM::blank();
+	vm.set_registers(Registers { pc: PC, rp: RP, sp: SP0, t });
+	vm.core[PC as usize] = instruction;
+	vm.core[SP0 as usize] = n;
+	vm.core[SP0 as usize - 1] = BELOW;
+	vm.core[RP as usize] = RETURN;
+	vm.core[CELL as usize] = BEEF;
+	vm
+}
+
+/// `delta` is the amount a stack delta field adds to a stack pointer.
+fn delta(bits: u16) -> u16 { [0, 1, 0xfffe, 0xffff][bits as usize & 3] }
+
+#[test]
+fn alu() {
+	let rows = rows();
+	for op in 0..32 {
+		assert!(op == 27 || rows.iter().any(|r| r.op == op), "no row for {}", op);
+	}
+	for r in &rows {
+		for bits in 0..=0xff {
+			let instruction = 0x6000 | (r.op << 8) | bits;
+			let mut vm = start(instruction, r.t, r.n);
+			let (mut saved, mut input, mut output) = (Saved(Vec::new()), &[INPUT][..], Vec::new());
+			let cells: Vec<u16> = vm.core[..4].to_vec();
+			assert_eq!(vm.step(1, &mut saved, &mut input, &mut output), Outcome::Running, "{:04x}", instruction);
+
+			let mut core = start(instruction, r.t, r.n).core;
+			let sp = r.sp.wrapping_add(delta(bits));
+			let rp = r.rp.wrapping_sub(delta(bits >> 2));
+			let pc = if r.trap { 1 } else if bits & 0x10 != 0 { RETURN >> 1 } else { PC + 1 };
+			let t = if bits & 0x20 != 0 { r.tn.1 } else { r.top };
+			if let Some((a, v)) = r.write { core[a as usize] = v }
+			if bits & 0x40 != 0 { core[rp as usize] = r.tn.0 }
+			if bits & 0x80 != 0 { core[sp as usize] = r.tn.0 }
+
+			assert_eq!(vm.registers(), Registers { pc, rp, sp, t }, "{:04x}", instruction);
+			assert!(vm.core[..] == core[..], "{:04x}", instruction);
+			assert_eq!(saved.0, if r.op == 22 { cells } else { Vec::new() }, "{:04x}", instruction);
+			assert_eq!(output, if r.op == 23 { vec![b'A'] } else { Vec::new() }, "{:04x}", instruction);
+			assert_eq!(input.is_empty(), r.op == 24, "{:04x}", instruction);
+		}
+	}
+}
+
+#[test]
+fn alu_failures() {
+	/* the I/O operations give -1 when they fail */
+	let mut vm = start(0x7700, 0x41, 0);
+	assert_eq!(vm.step(1, &mut (), &mut (), &mut Broken), Outcome::Running);
+	assert_eq!(vm.t, 0xffff);
+
+	let mut vm = start(0x7800, 0x1234, 0);
+	assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.t, 0xffff);
+
+	let mut vm = start(0x7600, 7, 0);
+	assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.t, 0xffff);
+
+	/* and so do the block operations on a buffer past the end of memory */
+	let mut vm = start(0x7c00, 0, 0xfc02);
+	assert_eq!(vm.step(1, &mut Disk([b'x'; BLOCK_SIZE]), &mut (), &mut ()), Outcome::Running);
+	assert!((vm.t, vm.core[0x7e01]) == (0xffff, 0));
+	let mut vm = start(0x7d00, 0, 0xfc02);
+	assert_eq!(vm.step(1, &mut Disk([b'x'; BLOCK_SIZE]), &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.t, 0xffff);
+}
+
+/// `Disk` holds a single block, number zero.
+struct Disk([u8; BLOCK_SIZE]);
+
+impl Store for Disk {
+	fn store(&mut self, _cells: &[u16], _order: ByteOrder) -> bool { false }
+
+	fn read_block(&mut self, block: u16, buffer: &mut [u8; BLOCK_SIZE]) -> bool {
+		if block == 0 { buffer.copy_from_slice(&self.0) }
+		block == 0
+	}
+
+	fn write_block(&mut self, block: u16, buffer: &[u8; BLOCK_SIZE]) -> bool {
+		if block == 0 { self.0.copy_from_slice(buffer) }
+		block == 0
+	}
+}
+
+#[test]
+fn blocks() {
+	/* a block is read into the cells of the buffer with the first character low */
+	let mut disk = Disk([0; BLOCK_SIZE]);
+	disk.0[..4].copy_from_slice(b"ABCD");
+	disk.0[BLOCK_SIZE - 1] = b'z';
+	let mut vm = start(0x7c00, 0, CELL << 1);
+	assert_eq!(vm.step(1, &mut disk, &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.t, 0);
+	assert_eq!(&vm.core[CELL as usize..CELL as usize + 3], &[0x4241, 0x4443, 0]);
+	assert_eq!(vm.core[CELL as usize + BLOCK_SIZE / 2 - 1], 0x7a00);
+	assert_eq!(vm.core[CELL as usize + BLOCK_SIZE / 2], 0);
+
+	/* and written back the same way, whatever the byte order of the image */
+	vm.core[CELL as usize] = 0x6261;
+	vm.set_byte_order(ByteOrder::Big);
+	vm.set_registers(Registers { pc: PC, rp: RP, sp: SP0, t: 0 });
+	vm.core[PC as usize] = 0x7d00;
+	assert_eq!(vm.step(1, &mut disk, &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.t, 0);
+	assert_eq!(&disk.0[..4], b"abCD");
+	assert_eq!(disk.0[BLOCK_SIZE - 1], b'z');
+
+	/* there is no block one */
+	let mut vm = start(0x7c00, 1, CELL << 1);
+	assert_eq!(vm.step(1, &mut disk, &mut (), &mut ()), Outcome::Running);
+	assert!((vm.t, vm.core[CELL as usize]) == (0xffff, BEEF));
+}
+
+#[test]
+fn bye() {
+	for bits in 0..=0xff {
+		let instruction = 0x7b00 | bits;
+		let mut vm = start(instruction, 0xfffe, 0x5678);
+		assert_eq!(vm.step(10, &mut (), &mut (), &mut ()), Outcome::Halted(-2));
+		assert_eq!(vm.registers(), Registers { pc: PC, rp: RP, sp: SP0, t: 0xfffe });
+		assert!(vm.core[..] == start(instruction, 0xfffe, 0x5678).core[..]);
+	}
+}
+
+/// `Case` is a test of an instruction other than an ALU one.
+struct Case {
+	instruction: u16,
+	t: u16,
+	registers: Registers,
+	/// `write` is the cell written, if any
+	write: Option<(u16, u16)>,
+}
+
+#[test]
+fn others() {
+	let cases = [
+		Case { instruction: 0x8000, t: 0x1234, registers: Registers { pc: PC + 1, rp: RP, sp: SP0 + 1, t: 0 }, write: Some((SP0 + 1, 0x1234)) },
+		Case { instruction: 0xffff, t: 0x1234, registers: Registers { pc: PC + 1, rp: RP, sp: SP0 + 1, t: 0x7fff }, write: Some((SP0 + 1, 0x1234)) },
+		Case { instruction: 0x4123, t: 0x1234, registers: Registers { pc: 0x123, rp: RP - 1, sp: SP0, t: 0x1234 }, write: Some((RP - 1, (PC + 1) << 1)) },
+		Case { instruction: 0x5fff, t: 0x1234, registers: Registers { pc: 0x1fff, rp: RP - 1, sp: SP0, t: 0x1234 }, write: Some((RP - 1, (PC + 1) << 1)) },
+		Case { instruction: 0x0123, t: 0x1234, registers: Registers { pc: 0x123, rp: RP, sp: SP0, t: 0x1234 }, write: None },
+		Case { instruction: 0x2123, t: 0, registers: Registers { pc: 0x123, rp: RP, sp: SP0 - 1, t: 0x5678 }, write: None },
+		Case { instruction: 0x2123, t: 1, registers: Registers { pc: PC + 1, rp: RP, sp: SP0 - 1, t: 0x5678 }, write: None },
+		Case { instruction: 0x2123, t: 0xffff, registers: Registers { pc: PC + 1, rp: RP, sp: SP0 - 1, t: 0x5678 }, write: None },
+	];
+	for c in &cases {
+		let mut vm = start(c.instruction, c.t, 0x5678);
+		assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+		assert_eq!(vm.registers(), c.registers, "{:04x}", c.instruction);
+		let mut core = start(c.instruction, c.t, 0x5678).core;
+		if let Some((a, v)) = c.write { core[a as usize] = v }
+		assert!(vm.core[..] == core[..], "{:04x}", c.instruction);
+	}
+}
+
+#[test]
+fn trap() {
+	/* the trap handler at one is reached from division by zero, with 10 on the stack */
+	let mut vm = VM::blank();
+	let image = [0x0000, 0x7b00 /* bye */, 0x8007, 0x8000, 0x7900 /* um/mod */];
+	vm.core[..image.len()].copy_from_slice(&image);
+	vm.pc = 2;
+	assert_eq!(vm.execute(&mut (), &mut (), &mut ()), 10);
+	assert_eq!(vm.depth(), 2);
+	assert_eq!(vm.pop(), Some(10));
+	assert_eq!(vm.pop(), Some(7));
+}
+
+#[test]
+fn wrap() {
+	/* addresses wrap around at the end of memory */
+	let mut vm = start(0x8005, 0x1234, 0);
+	vm.sp = 0xffff;
+	assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+	assert_eq!((vm.sp, vm.core[0]), (0, 0x1234));
+
+	let mut vm = start(0x6000, 0, 0);
+	vm.pc = CORE_SIZE as u16 - 1;
+	vm.core[CORE_SIZE - 1] = 0x6000;
+	assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+	assert_eq!(vm.pc, 0);
+}
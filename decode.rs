@@ -0,0 +1,249 @@
+//! # Decoded Instructions
+//!
+//! Each instruction is a single cell, `Op::decode` splits one into its class
+//! and fields, and is what the interpreter in `VM::step` does every cycle.
+//! With the `std` feature `Predecoded` keeps a copy of memory decoded ahead
+//! of time alongside a virtual machine, so that the work of decoding is only
+//! done once for each instruction. An entry is thrown away whenever the cell
+//! it was decoded from is written to, by the program or the host, and is
+//! decoded again the next time it is executed.
+
+#[cfg(feature = "std")]
+use host::{Input, Output, Store};
+#[cfg(feature = "std")]
+use {Outcome, VM, CORE_SIZE};
+
+/// `DELTA` maps the two bit stack delta fields of an ALU instruction on to
+/// the amount added to a stack pointer.
+const DELTA: [u16; 4] = [0, 1, 0xfffe, 0xffff];
+
+/// `Op` is a decoded instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+	/// An entry in `Predecoded` that has not been decoded, or has been
+	/// written to since it was
+	Undecoded,
+	/// Push a 15-bit value
+	Literal(u16),
+	/// An ALU operation
+	Alu(Alu),
+	/// Call a word, pushing the return address
+	Call(u16),
+	/// Branch if the top of the stack is zero, popping it
+	ZeroBranch(u16),
+	/// Unconditional branch
+	Branch(u16),
+}
+
+/// `Alu` holds the fields of an ALU instruction, with the stack deltas
+/// already looked up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alu {
+	/// `op` is the operation, zero to 31
+	pub op: u8,
+	/// `flags` are the `R_TO_PC`, `N_TO_T`, `T_TO_R` and `T_TO_N` bits
+	pub flags: u8,
+	/// `dstack` is added to the variable stack pointer
+	pub dstack: u16,
+	/// `rstack` is subtracted from the return stack pointer
+	pub rstack: u16,
+}
+
+impl Alu {
+	/// Return, the program counter is loaded from the return stack
+	pub const R_TO_PC: u8 = 0x10;
+	/// The next on stack is copied to the top of stack
+	pub const N_TO_T: u8 = 0x20;
+	/// The top of stack is copied to the return stack
+	pub const T_TO_R: u8 = 0x40;
+	/// The top of stack is copied to the next on stack
+	pub const T_TO_N: u8 = 0x80;
+
+	/// `reads_input` is true for the operation that reads a character.
+	pub fn reads_input(self) -> bool { self.op == 24 }
+}
+
+impl Op {
+	/// `decode` splits `instruction` into its fields.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use embed::decode::{Alu, Op};
+	/// assert_eq!(Op::decode(0x8005), Op::Literal(5));
+	/// assert_eq!(Op::decode(0x6523), Op::Alu(Alu { op: 5, flags: Alu::N_TO_T, dstack: 0xffff, rstack: 0 }));
+	/// ```
+	#[inline(always)]
+	pub fn decode(instruction: u16) -> Op {
+		let target = instruction & 0x1fff;
+		if instruction & 0x8000 == 0x8000 { return Op::Literal(instruction & 0x7fff) }
+		match instruction & 0xe000 {
+			0x6000 => Op::Alu(Alu {
+				op: ((instruction >> 8) & 0x1f) as u8,
+				flags: (instruction & 0xf0) as u8,
+				dstack: DELTA[(instruction & 0x3) as usize],
+				rstack: DELTA[((instruction >> 2) & 0x3) as usize],
+			}),
+			0x4000 => Op::Call(target),
+			0x2000 => Op::ZeroBranch(target),
+			_ => Op::Branch(target),
+		}
+	}
+
+	/// `reads_input` is true if executing the instruction reads a character.
+	#[inline(always)]
+	pub fn reads_input(self) -> bool {
+		match self { Op::Alu(alu) => alu.reads_input(), _ => false }
+	}
+}
+
+/// `Predecoded` runs a virtual machine from a cache of decoded instructions,
+/// it behaves exactly as running the virtual machine with `VM::step` does.
+/// Memory has to be written through `write`, or `vm_mut`, for the cache to
+/// be kept up to date.
+///
+/// # Example
+///
+/// ```
+/// let vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+/// let mut fast = embed::decode::Predecoded::new(vm);
+/// let mut input: &[u8] = b"2 3 + . 7 (bye)\n";
+/// assert_eq!(fast.execute(&mut (), &mut input, &mut ()), 7);
+/// ```
+#[cfg(feature = "std")]
+pub struct Predecoded {
+	vm: VM,
+	ops: Box<[Op]>,
+}
+
+#[cfg(feature = "std")]
+impl Predecoded {
+	/// `new` decodes all of the memory of `vm`.
+	pub fn new(vm: VM) -> Self {
+		let ops = vm.core.iter().map(|&i| Op::decode(i)).collect();
+		Predecoded { vm, ops }
+	}
+
+	/// `vm` returns the virtual machine.
+	pub fn vm(&self) -> &VM { &self.vm }
+
+	/// `vm_mut` returns the virtual machine for modification, which throws
+	/// away all of the decoded instructions.
+	pub fn vm_mut(&mut self) -> &mut VM {
+		for op in self.ops.iter_mut() { *op = Op::Undecoded }
+		&mut self.vm
+	}
+
+	/// `into_inner` returns the virtual machine.
+	pub fn into_inner(self) -> VM { self.vm }
+
+	/// `read` returns the cell at `address`, as `VM::read` does.
+	pub fn read(&self, address: u16) -> u16 { self.vm.read(address) }
+
+	/// `write` sets the cell at `address`, as `VM::write` does.
+	pub fn write(&mut self, address: u16, value: u16) {
+		self.vm.write(address, value);
+		self.ops[address as usize % CORE_SIZE] = Op::Undecoded;
+	}
+
+	/// `execute` runs until the virtual machine halts, as `VM::execute`.
+	pub fn execute(&mut self, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> i32 {
+		loop {
+			if let Outcome::Halted(r) = self.step(u64::MAX, store, input, output) { return r }
+		}
+	}
+
+	/// `step` executes at most `cycles` instructions, as `VM::step`.
+	pub fn step(&mut self, cycles: u64, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> Outcome {
+		let vm = &mut self.vm;
+		let ops = &mut self.ops;
+		let mut r = vm.registers();
+		let (order, tracing, mut count) = (vm.order, vm.tracing, vm.count);
+		let (mut cycles, mut outcome) = (cycles, Outcome::Running);
+
+		if tracing && count == 0 { VM::header(&mut std::io::stderr()) }
+
+		let m = &mut vm.core;
+		while cycles > 0 {
+			let mut op = ops[r.pc as usize];
+			if op == Op::Undecoded {
+				op = Op::decode(m[r.pc as usize]);
+				ops[r.pc as usize] = op;
+			}
+			if op.reads_input() && !input.available() { /* input, suspend until available */
+				outcome = Outcome::NeedInput;
+				break;
+			}
+			cycles -= 1;
+
+			if tracing { VM::csv(&mut std::io::stderr(), &mut count, r.pc, m[r.pc as usize], r.t, r.sp, r.rp) }
+
+			if VM::exec(op, &mut r, m, order, store, input, output, &mut |a| ops[a as usize] = Op::Undecoded) {
+				outcome = Outcome::Halted((r.t as i16) as i32);
+				break;
+			}
+		}
+
+		vm.set_registers(r);
+		vm.count = count;
+		outcome
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decode() {
+		assert_eq!(Op::decode(0x8000), Op::Literal(0));
+		assert_eq!(Op::decode(0xffff), Op::Literal(0x7fff));
+		assert_eq!(Op::decode(0x4123), Op::Call(0x123));
+		assert_eq!(Op::decode(0x2123), Op::ZeroBranch(0x123));
+		assert_eq!(Op::decode(0x0123), Op::Branch(0x123));
+		assert_eq!(Op::decode(0x601c), Op::Alu(Alu { op: 0, flags: Alu::R_TO_PC, dstack: 0, rstack: 0xffff }));
+		assert_eq!(Op::decode(0x7881), Op::Alu(Alu { op: 24, flags: Alu::T_TO_N, dstack: 1, rstack: 0 }));
+		assert!(Op::decode(0x7881).reads_input());
+		assert!(!Op::decode(0x7b00).reads_input());
+	}
+
+	#[test]
+	#[cfg(all(feature = "std", feature = "eforth"))]
+	fn predecoded() {
+		/* changes a literal in a word after it has been executed and decoded */
+		let source = b"decimal : x 2 3 + . ; x ' x cell+ @ . $8007 ' x cell+ ! x 9 (bye)\n";
+		let mut plain = VM::new();
+		let mut output = Vec::new();
+		let code = plain.execute(&mut (), &mut &source[..], &mut output);
+
+		let mut fast = Predecoded::new(VM::new());
+		let mut decoded = Vec::new();
+		assert_eq!(fast.execute(&mut (), &mut &source[..], &mut decoded), code);
+		assert_eq!(code, 9);
+		assert_eq!(decoded, output);
+		let text = String::from_utf8_lossy(&decoded);
+		assert!(text.contains(" 5") && text.contains(" 9"), "{}", text);
+		assert_eq!(fast.vm().core[..], plain.core[..]);
+		assert_eq!(fast.vm().registers(), plain.registers());
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn invalidate() {
+		/* overwrites the literal at cell 4 with a no-op after it is decoded */
+		let image = [0x8000 | 0x6000, 0x8008, 0x6403 /* ! */, 0x802a, 0x8005, 0x7b00 /* bye */];
+		let mut fast = Predecoded::new(VM::blank());
+		for (i, &cell) in image.iter().enumerate() { fast.write(i as u16, cell) }
+		assert_eq!(fast.read(4), 0x8005);
+		assert_eq!(fast.execute(&mut (), &mut (), &mut ()), 42);
+		assert_eq!(fast.read(4), 0x6000);
+
+		fast.vm_mut().reset();
+		fast.write(3, 0x8007);
+		assert_eq!(fast.execute(&mut (), &mut (), &mut ()), 7);
+
+		fast.vm_mut().reset();
+		fast.vm_mut().write(3, 0x8008);
+		assert_eq!(fast.execute(&mut (), &mut (), &mut ()), 8);
+	}
+}
@@ -1,9 +1,43 @@
 #![crate_name = "embed"]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(any(feature = "std", test))]
+extern crate core;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(test)]
+extern crate proptest;
+
+#[cfg(feature = "std")]
 use std::io::prelude::*;
-use std::fs::File;
-use std::path::Path;
-mod eforth;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+mod images;
+pub mod image;
+#[cfg(feature = "std")]
+pub mod format;
+pub mod host;
+pub mod decode;
+pub mod reference;
+#[cfg(feature = "std")]
+pub mod fuse;
+#[cfg(feature = "std")]
+pub mod aot;
+#[cfg(feature = "std")]
+mod translate;
+#[cfg(feature = "tokio")]
+pub mod task;
+#[cfg(feature = "std")]
+pub mod block;
+
+pub use image::{ByteOrder, ImageError, ImageHeader};
+#[cfg(feature = "std")]
+pub use format::Format;
+pub use images::ImageId;
+pub use host::{Input, Output, Store, BLOCK_SIZE};
+use decode::{Alu, Op};
+#[cfg(feature = "tokio")]
+pub use task::Task;
 
 /// * `CORE_SIZE` is the total number of cells addressable by the virtual machine
 const CORE_SIZE: usize = 0x8000;
@@ -11,45 +45,41 @@ const CORE_SIZE: usize = 0x8000;
 const SP0: u16 = 0x2200;
 /// * `RP0` is the starting point of the return stack
 const RP0: u16 = 0x7fff;
+/// * `SLICE` is the number of instructions `run_limited` executes between
+///   checks of the clock
+const SLICE: u64 = 0x10000;
 
-/// `fputc` writes a single character of output to a file, and returns
-/// all bits set on an error. It emulates the C function of the same name,
-/// and is not a recommended way to output data in Rust, but is required for
-/// the VM.
-///
-/// # Arguments
-///
-/// * `output`  - Output stream to write to
-/// * `t`       - Single byte to write
-///
-/// # Returns
-///
-/// This function returns `t` on success and `0xffff` on error
-///
-fn fputc(output: &mut dyn Write, t: u8) -> u16 {
-	let u: [u8; 1] = [t as u8];
-	if 1 == output.write(&u).unwrap() { t as u16 } else { 0xffff }
+/// `Outcome` is the reason `VM::step` returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+	/// The cycle limit was reached, the virtual machine is still running.
+	Running,
+	/// The virtual machine is waiting on input, it stopped at the instruction
+	/// that reads a character, which is executed again when it is resumed.
+	NeedInput,
+	/// The virtual machine halted, with the value it halted with.
+	Halted(i32),
+	/// A limit given to `VM::run_limited` was reached, the virtual machine
+	/// can be resumed as it is for `Running`.
+	Timeout,
 }
 
-/// `fputc` gets a single character from an input stream, like the C function
-/// with the same name, it returns all bits set (-1) on error. This is not a
-/// very idiomatic way of doing things from a Rust point of view, but this
-/// function is used by the virtual machine to get input, and it expects
-/// errors to be signaled in the message.
-///
-/// # Arguments
-///
-/// `input` - Input stream to read from
-///
-/// # Returns
-///
-/// This function returns a single byte on success in the lower half a
-/// 16-bit value, and all bits set (or `0xffff`) on failure.
-fn fgetc(input: &mut dyn Read) -> u16 {
-	let mut u: [u8; 1] = [0];
-	if 1 == input.read(&mut u).unwrap() { u[0] as u16 } else { 0xffff }
+/// `Limits` bounds how long `VM::run_limited` may run for, a limit of `None`
+/// is no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+	/// `cycles` is the maximum number of instructions to execute
+	pub cycles: Option<u64>,
+	/// `time` is the maximum wall clock time to run for, it is checked every
+	/// few thousand instructions, so it cannot interrupt a blocking read
+	#[cfg(feature = "std")]
+	pub time: Option<Duration>,
 }
 
+/// `Registers` holds the registers of the virtual machine while it runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Registers { pc: u16, rp: u16, sp: u16, t: u16 }
+
 /// # Embed Virtual Machine in Rust
 ///
 /// * LICENSE:    MIT
@@ -68,12 +98,17 @@ fn fgetc(input: &mut dyn Read) -> u16 {
 /// virtual machine architecture).
 /// 
 /// * TODO: Implement Index trait for u16?
+#[derive(Clone)]
 pub struct VM {
 	/// `tracing` can be set true to enable logging, logging is very verbose
 	tracing: bool,
 	/// `count` is the number instructions executed so far, it is only updated
-	/// if tracing is on.
+	/// if tracing is on, which requires the `std` feature.
+	#[cfg_attr(not(feature = "std"), allow(dead_code))]
 	count: u64,
+	/// `order` is the byte order used when saving, it is set by `load` to
+	/// the byte order of the last image loaded.
+	order: ByteOrder,
 	/// The virtual machine has minimal state, a program counter (`pc`),
 	/// a return stack pointer `rp`, a data stack pointer `sp` and a top
 	/// of stack pointer `t`.
@@ -88,16 +123,53 @@ impl VM {
 
 	/// `new` constructs a new virtual machine image that can be passed to `run`
 	/// straight away, as the program memory is copied from a default image
-	/// that contains an eForth interpreter.
+	/// that contains an eForth interpreter. If the `eforth` feature is
+	/// disabled the program memory is left empty instead.
 	pub fn new() -> Self { 
-		let mut r = VM { tracing: false, count: 0, pc: 0, rp: RP0, sp: SP0, t: 0, core: [0; CORE_SIZE] };
+		#[cfg(feature = "eforth")]
+		return VM::with_image(ImageId::Eforth);
+		#[cfg(not(feature = "eforth"))]
+		return VM::blank();
+	}
 
-		for i in 0..eforth::EFORTH_CORE.len() {
-			r.core[i] = eforth::EFORTH_CORE[i];
-		}
+	/// `with_image` constructs a new virtual machine with one of the images
+	/// bundled with the library.
+	///
+	/// # Arguments
+	///
+	/// * `image` - Bundled image to copy into program memory
+	///
+	pub fn with_image(image: ImageId) -> Self {
+		let mut r = VM::blank();
+		let cells = image.cells();
+		r.core[..cells.len()].copy_from_slice(cells);
 		r
 	}
 
+	/// `from_bytes` constructs a new virtual machine from an image held in
+	/// memory, the image is validated as it would be by `load`.
+	///
+	/// # Arguments
+	///
+	/// * `bytes` - Raw image, in either byte order
+	///
+	/// # Example
+	///
+	/// ```
+	/// let image = include_bytes!("eforth.blk");
+	/// let vm = embed::VM::from_bytes(image).unwrap();
+	/// assert_eq!(vm.image_header().unwrap().version(), 0x1984);
+	/// ```
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+		let mut r = VM::blank();
+		r.load_bytes(bytes)?;
+		Ok(r)
+	}
+
+	fn blank() -> Self {
+		VM { tracing: false, count: 0, order: ByteOrder::Little, pc: 0, rp: RP0, sp: SP0, t: 0, core: [0; CORE_SIZE] }
+	}
+
 	/// `reset` sets the VMs registers back to their defaults, it does not
 	/// reset the program memory or the stack contents, but the stack pointers,
 	/// top of stack register, and the program counter.
@@ -119,6 +191,61 @@ impl VM {
 		self.tracing = state;
 	}
 
+	/// `set_byte_order` sets the byte order used by `save` and by the save
+	/// instruction, and the order `load` falls back to if it cannot detect the
+	/// byte order of an image from its header.
+	///
+	/// # Arguments
+	///
+	/// * `order` - Byte order of cells in images
+	///
+	pub fn set_byte_order(&mut self, order: ByteOrder) {
+		self.order = order;
+	}
+
+	/// `byte_order` returns the byte order currently in use, see `set_byte_order`.
+	pub fn byte_order(&self) -> ByteOrder {
+		self.order
+	}
+
+	/// `push` a value onto the variable stack, the top of stack is kept in
+	/// a register and the rest of the stack in `core`, as the virtual
+	/// machine would do it.
+	pub fn push(&mut self, value: u16) {
+		self.sp = self.sp.wrapping_add(1);
+		self.core[self.sp as usize % CORE_SIZE] = self.t;
+		self.t = value;
+	}
+
+	/// `pop` a value from the variable stack.
+	///
+	/// # Returns
+	///
+	/// The value popped, or `None` if the stack is empty.
+	pub fn pop(&mut self) -> Option<u16> {
+		if self.depth() == 0 { return None }
+		let value = self.t;
+		self.t = self.core[self.sp as usize % CORE_SIZE];
+		self.sp = self.sp.wrapping_sub(1);
+		Some(value)
+	}
+
+	/// `depth` is the number of items on the variable stack.
+	pub fn depth(&self) -> u16 {
+		self.sp.wrapping_sub(SP0)
+	}
+
+	/// `read` returns the cell at `address`, which is a cell address and not
+	/// a byte address as used by Forth.
+	pub fn read(&self, address: u16) -> u16 {
+		self.core[address as usize % CORE_SIZE]
+	}
+
+	/// `write` sets the cell at `address`, see `read`.
+	pub fn write(&mut self, address: u16, value: u16) {
+		self.core[address as usize % CORE_SIZE] = value;
+	}
+
 	/// `run` executes the virtual machine on the currently loaded program
 	/// in `core`. The specification for the virtual machine is too long
 	/// for this document, but visit <https://github.com/howerj/embed> for
@@ -146,49 +273,176 @@ impl VM {
 	/// The save opcode will save to the file *new.blk*. What the VM will
 	/// do depends entirely on the code in the *eforth.blk* file.
 	///
-	/// ```
+	/// ```no_run
 	/// extern crate embed;
 	/// use std::fs::File;
 	/// use std::path::Path;
 	/// 
 	/// let mut evm = embed::VM::new();
 	/// let mut file = File::open(&Path::new("vm.blk")).unwrap();
-	/// evm.load(&mut file);
+	/// evm.load(&mut file).unwrap();
 	/// evm.run(Some("new.blk"), &mut std::io::stdin(), &mut std::io::stdout());
 	/// ```
 	/// 
+	#[cfg(feature = "std")]
 	pub fn run(&mut self, block: Option<&str>, input: &mut dyn Read, output: &mut dyn Write) -> i32 {
-		let (mut pc, mut rp, mut sp, mut t) = (self.pc, self.rp, self.sp, self.t);
-		let mut d: u32;
-		let mut m = self.core;
+		self.execute(&mut host::FileStore(block), &mut host::Reader(input), &mut host::Writer(output))
+	}
 
-		self.header(&mut std::io::stderr());
+	/// `execute` is `run` for hosts without the standard library, all I/O
+	/// and the saving of memory goes through the traits in the `host` module.
+	///
+	/// # Arguments
+	///
+	/// * `store`  - Where the save instruction writes memory to, `()` to disallow saving
+	/// * `input`  - Source of input characters
+	/// * `output` - Sink for output characters
+	///
+	/// # Returns
+	///
+	/// The same as `run`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// let mut vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+	/// let mut input: &[u8] = b"2 3 + . bye ";
+	/// vm.execute(&mut (), &mut input, &mut ());
+	/// ```
+	pub fn execute(&mut self, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> i32 {
+		loop {
+			if let Outcome::Halted(r) = self.step(u64::MAX, store, input, output) { return r }
+		}
+	}
+
+	/// `run_limited` is `execute` with a watchdog, it runs until the virtual
+	/// machine halts, needs input it does not have, or one of `limits` is
+	/// reached. The limits apply to each call, and the virtual machine is
+	/// left so that calling it again carries on where it stopped.
+	///
+	/// # Arguments
+	///
+	/// * `limits` - Maximum number of instructions and time to run for
+	/// * `store`  - Where the save instruction writes memory to
+	/// * `input`  - Source of input characters
+	/// * `output` - Sink for output characters
+	///
+	/// # Returns
+	///
+	/// `Outcome::Timeout` if a limit was reached, otherwise the same as `step`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// let mut vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+	/// let mut input: &[u8] = b": x begin again ; x\n";
+	/// let limits = embed::Limits { cycles: Some(1_000_000), ..Default::default() };
+	/// assert_eq!(vm.run_limited(&limits, &mut (), &mut input, &mut ()), embed::Outcome::Timeout);
+	/// ```
+	pub fn run_limited(&mut self, limits: &Limits, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> Outcome {
+		#[cfg(feature = "std")]
+		let start = Instant::now();
+		let mut remaining = limits.cycles.unwrap_or(u64::MAX);
+		while remaining > 0 {
+			let cycles = remaining.min(SLICE);
+			match self.step(cycles, store, input, output) {
+				Outcome::Running => remaining -= cycles,
+				outcome => return outcome,
+			}
+			#[cfg(feature = "std")]
+			if limits.time.is_some_and(|time| start.elapsed() >= time) { break }
+		}
+		Outcome::Timeout
+	}
+
+	/// `step` executes at most `cycles` instructions, the virtual machine can
+	/// be resumed by calling `step` again as all state is kept in `self`.
+	///
+	/// # Arguments
+	///
+	/// * `cycles` - Maximum number of instructions to execute
+	/// * `store`  - Where the save instruction writes memory to
+	/// * `input`  - Source of input characters
+	/// * `output` - Sink for output characters
+	///
+	/// # Returns
+	///
+	/// `Outcome::Halted` with the value `run` would return if the virtual
+	/// machine halted, `Outcome::NeedInput` if it stopped early because it
+	/// needs input and `input` has none available, all registers are kept
+	/// so it resumes at the instruction reading input, and `Outcome::Running`
+	/// if it ran for all `cycles`.
+	pub fn step(&mut self, cycles: u64, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> Outcome {
+		let mut r = self.registers();
+		#[cfg_attr(not(feature = "std"), allow(unused_variables, unused_mut))]
+		let (order, tracing, mut count) = (self.order, self.tracing, self.count);
+		let (mut cycles, mut outcome) = (cycles, Outcome::Running);
+
+		#[cfg(feature = "std")]
+		if tracing && count == 0 { VM::header(&mut std::io::stderr()) }
+
+		let m = &mut self.core;
+		while cycles > 0 {
+			let instruction = m[r.pc as usize];
+			let op = Op::decode(instruction);
+			if op.reads_input() && !input.available() { /* input, suspend until available */
+				outcome = Outcome::NeedInput;
+				break;
+			}
+			cycles -= 1;
 
-		'eval: loop {
-			let instruction = m[pc as usize];
-			const DELTA: [u16; 4] = [0, 1, 0xfffe, 0xffff];
+			#[cfg(feature = "std")]
+			if tracing { VM::csv(&mut std::io::stderr(), &mut count, r.pc, instruction, r.t, r.sp, r.rp) }
 
-			VM::csv(self, &mut std::io::stderr(), pc, instruction, t, sp, rp);
+			if VM::exec(op, &mut r, m, order, store, input, output, &mut |_| { }) {
+				outcome = Outcome::Halted((r.t as i16) as i32);
+				break;
+			}
+		}
 
-			if 0x8000 & instruction == 0x8000 { /* literal */
-				sp += 1;
-				m[sp as usize] = t;
-				t = instruction & 0x7fff;
-				pc += 1;
-			} else if 0xe000 & instruction == 0x6000 { /* ALU */
+		self.set_registers(r);
+		self.count = count;
+
+		outcome
+	}
+
+	/// `exec` executes a single decoded instruction, it is shared by `step`
+	/// and `decode::Predecoded::step` so that both behave identically. The
+	/// address of every cell written to is passed to `written`. Addresses
+	/// wrap around at the end of memory and arithmetic wraps, so that no
+	/// instruction can panic, whatever state the virtual machine is in.
+	///
+	/// # Returns
+	///
+	/// True if the instruction halts the virtual machine, in which case the
+	/// registers are left as they were.
+	#[inline(always)]
+	#[allow(clippy::too_many_arguments)]
+	fn exec<F: FnMut(u16)>(op: Op, r: &mut Registers, m: &mut [u16; CORE_SIZE], order: ByteOrder, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output, written: &mut F) -> bool {
+		let Registers { mut pc, mut rp, mut sp, mut t } = *r;
+		let at = |a: u16| a & (CORE_SIZE as u16 - 1); /* addresses wrap around at the end of memory */
+		let d: u32;
+		match op {
+			Op::Literal(value) => {
+				sp = sp.wrapping_add(1);
+				m[at(sp) as usize] = t;
+				written(at(sp));
+				t = value;
+				pc = at(pc + 1);
+			}
+			Op::Alu(alu) => {
 				let mut tp = t;
-				let mut n = m[sp as usize];
-				pc = if instruction & 0x10 == 0x10 { m[rp as usize] >> 1 } else { pc + 1 };
+				let mut n = m[at(sp) as usize];
+				pc = if alu.flags & Alu::R_TO_PC != 0 { m[at(rp) as usize] >> 1 } else { at(pc + 1) };
 
-				let alu = ((instruction >> 8) & 0x1f) as u8;
-				match alu {
+				match alu.op {
 					0  => { /* tp = t */ }
 					1  => { tp = n }
-					2  => { tp = m[rp as usize] }
+					2  => { tp = m[at(rp) as usize] }
 					3  => { tp = m[(t >> 1) as usize] }
-					4  => { m[(t >> 1) as usize] = n; sp = sp - 1; tp = m[sp as usize] }
-					5  => { d = (t as u32) + (n as u32); tp = (d >> 16) as u16; m[sp as usize] = d as u16; n = d as u16 }
-					6  => { d = (t as u32) * (n as u32); tp = (d >> 16) as u16; m[sp as usize] = d as u16; n = d as u16 }
+					4  => { m[(t >> 1) as usize] = n; written(t >> 1); sp = sp.wrapping_sub(1); tp = m[at(sp) as usize] }
+					5  => { d = (t as u32) + (n as u32); tp = (d >> 16) as u16; m[at(sp) as usize] = d as u16; written(at(sp)); n = d as u16 }
+					6  => { d = (t as u32) * (n as u32); tp = (d >> 16) as u16; m[at(sp) as usize] = d as u16; written(at(sp)); n = d as u16 }
 					7  => { tp &= n }
 					8  => { tp |= n }
 					9  => { tp ^= n }
@@ -198,60 +452,71 @@ impl VM {
 					13 => { tp = if t == n { 0xffff } else { 0 } }
 					14 => { tp = if n  < t { 0xffff } else { 0 } }
 					15 => { tp = if (n as i16) < (t as i16) { 0xffff } else { 0 } }
-					16 => { tp = n >> t }
-					17 => { tp = n << t }
+					16 => { tp = n.checked_shr(t as u32).unwrap_or(0) }
+					17 => { tp = n.checked_shl(t as u32).unwrap_or(0) }
 					18 => { tp = sp << 1 }
 					19 => { tp = rp << 1 }
 					20 => { sp = t >> 1 }
 					21 => { rp = t >> 1; tp = n }
-					22 => { tp = self.save_file(block, n >> 1, (((t as u32) + 1) >> 1) as u16) } 
-					23 => { tp = fputc(output, t as u8) } 
-					24 => { tp = fgetc(input) }
-					25 => { if t != 0 { tp = n / t; t = n % t; n = t } else { pc = 1; tp = 10 } }
+					22 => { tp = VM::save_cells(store, order, &m[..], n >> 1, (((t as u32) + 1) >> 1) as u16) }
+					23 => { tp = if output.putc(t as u8) { t & 0xff } else { 0xffff } }
+					24 => { tp = input.getc().map_or(0xffff, |c| c as u16) }
+					25 => { if let Some(q) = n.checked_div(t) { tp = q; t = n % t; n = t } else { pc = 1; tp = 10 } }
 					26 => { 
 						if t != 0 { 
-							tp = ((n as i16) / (t as i16)) as u16; 
-							t = ((n as i16) % (t as i16)) as u16; 
+							tp = (n as i16).wrapping_div(t as i16) as u16; 
+							t = (n as i16).wrapping_rem(t as i16) as u16; 
 							n = t 
 						} else { pc = 1; tp = 10 } }
-					27 => { break 'eval; }
+					27 => { return true }
+					28 => { tp = VM::read_block(store, m, n >> 1, t, written) }
+					29 => { tp = VM::write_block(store, &m[..], n >> 1, t) }
 					_  => { }
 				}
 
-				sp = sp.wrapping_add(DELTA[ (instruction       & 0x3) as usize]);
-				rp = rp.wrapping_sub(DELTA[((instruction >> 2) & 0x3) as usize]);
-				if instruction & 0x20 == 0x20 { tp = n; }
-				if instruction & 0x40 == 0x40 { m[rp as usize] = t }
-				if instruction & 0x80 == 0x80 { m[sp as usize] = t }
+				sp = sp.wrapping_add(alu.dstack);
+				rp = rp.wrapping_sub(alu.rstack);
+				if alu.flags & Alu::N_TO_T != 0 { tp = n; }
+				if alu.flags & Alu::T_TO_R != 0 { m[at(rp) as usize] = t; written(at(rp)) }
+				if alu.flags & Alu::T_TO_N != 0 { m[at(sp) as usize] = t; written(at(sp)) }
 				t = tp;
-			} else if 0xe000 & instruction == 0x4000 { /* call */
-				rp -= 1;
-				m[rp as usize] = (pc + 1) << 1;
-				pc = instruction & 0x1fff;
-			} else if 0xe000 & instruction == 0x2000 { /* 0branch */
-				pc = if t == 0 { instruction & 0x1fff } else { pc + 1 };
-				t = m[sp as usize];
-				sp -= 1;
-			} else { /* branch */
-				pc = instruction & 0x1fff;
 			}
+			Op::Call(target) => {
+				rp = rp.wrapping_sub(1);
+				m[at(rp) as usize] = (pc + 1) << 1;
+				written(at(rp));
+				pc = target;
+			}
+			Op::ZeroBranch(target) => {
+				pc = if t == 0 { target } else { at(pc + 1) };
+				t = m[at(sp) as usize];
+				sp = sp.wrapping_sub(1);
+			}
+			Op::Branch(target) => { pc = target }
+			Op::Undecoded => unreachable!("executed an undecoded instruction"),
 		}
-	
-		self.pc = pc;
-		self.rp = rp;
-		self.sp = sp;
-		self.t  = t;
+		*r = Registers { pc, rp, sp, t };
+		false
+	}
+
+	fn registers(&self) -> Registers {
+		Registers { pc: self.pc, rp: self.rp, sp: self.sp, t: self.t }
+	}
 
-		(t as i16) as i32
+	fn set_registers(&mut self, r: Registers) {
+		self.pc = r.pc;
+		self.rp = r.rp;
+		self.sp = r.sp;
+		self.t  = r.t;
 	}
 
-	/// Print a header for a CSV file trace, if tracing is enabled, the output should be consumable
+	/// Print a header for a CSV file trace, when tracing is first enabled, the output should be consumable
 	/// by the utility <https://github.com/carlos-jenkins/csv2vcd> which can turn a CSV file into
 	/// a VCD (Value Change Dump) file. This file can be used with a suitable waveform viewer, such
 	/// as GTKWave <http://gtkwave.sourceforge.net/> for debugging purposes.
 	///
-	fn header(&self, output: &mut dyn Write) {
-		if !self.tracing { return }
+	#[cfg(feature = "std")]
+	fn header(output: &mut dyn Write) {
 		let _ignore = writeln!(output, "\"pc[15:0]\",\"instruction[15:0]\",\"t[15:0]\",\"sp[7:0]\",\"rp[7:0]\",\"TIME\"");
 	}
 
@@ -264,8 +529,8 @@ impl VM {
 	///
 	/// It should be noted that `csv` accepts the arguments it will print instead of printing
 	/// out the values stored in `self`, as the value for the VM state such as the program
-	/// counter and stack pointers are kept in locals until `run` returns, and only then are
-	/// they updated.
+	/// counter and stack pointers are kept in locals until `step` returns, and only then are
+	/// they updated. The instruction count is passed in for the same reason.
 	/// 
 	/// Arguments are logged in order, `pc` being the left most field in a record line and
 	/// `rp` the rightmost (of the values passed in, the rightmost field is actually a "time"
@@ -274,6 +539,7 @@ impl VM {
 	/// # Arguments
 	/// 
 	/// * `output`       - output stream to log to
+	/// * `count`        - number of instructions logged so far, incremented by one
 	/// * `pc`           - the program counter
 	/// * `instruction`  - the current instruction being executed, or `self->core[pc]`
 	/// * `t`            - top of stack register
@@ -281,40 +547,87 @@ impl VM {
 	/// * `rp`           - return stack pointer, index into `core`
 	/// 
 	/// 
-	fn csv(&mut self, output: &mut dyn Write, pc: u16, instruction: u16, t: u16, sp: u16, rp: u16) -> () {
-		if !self.tracing { return }
-		let time = if self.count == 0 { "s" } else { "ns" };
-		let _ignore = writeln!(output, "{}", format!("{:04x},{:04x},{:04x},{:02x},{:02x},{}{}", pc, instruction, t, sp, rp, self.count, time));
-		self.count += 1;
-	}
-
-	/// `save_file` is for internal use only, as it converts any errors into results understandable
-	/// by the virtual machine. Its purpose is to save optionally save
-	fn save_file(&self, block: Option<&str>, start: u16, length: u16) -> u16 {
-		let name = match block { None => return 0xffff, Some(name) => name };
-
-		let mut file = match File::create(&Path::new(name)) {
-			Err(r) => { println!("failed to create block \"{}\": {}", name, r); return 0xffff },
-			Ok(r) => r
-		};
-
-		match self.save_block(&mut file, start, length) {
-			None => 0xffff,
-			Some(r) => r
+	#[cfg(feature = "std")]
+	fn csv(output: &mut dyn Write, count: &mut u64, pc: u16, instruction: u16, t: u16, sp: u16, rp: u16) {
+		let time = if *count == 0 { "s" } else { "ns" };
+		let _ignore = writeln!(output, "{:04x},{:04x},{:04x},{:02x},{:02x},{}{}", pc, instruction, t, sp, rp, count, time);
+		*count += 1;
+	}
+
+	/// `section` is the range of cells saved given the first cell and the
+	/// number of cells, as the save instruction is given them, or `None` if
+	/// it runs past the end of `core`.
+	fn section(start: u16, length: u16) -> Option<core::ops::Range<usize>> {
+		let end = start as usize + length as usize;
+		if end > CORE_SIZE { return None }
+		Some((start as usize)..end)
+	}
+
+	/// `save_cells` is for internal use only, as it converts any errors into results understandable
+	/// by the virtual machine. Its purpose is to optionally save a section of memory to `store`.
+	fn save_cells(store: &mut dyn Store, order: ByteOrder, m: &[u16], start: u16, length: u16) -> u16 {
+		let saved = VM::section(start, length).is_some_and(|range| store.store(&m[range], order));
+		if saved { 0 } else { 0xffff }
+	}
+
+	/// `buffer` is the range of cells a block buffer starting at cell `start`
+	/// occupies, or `None` if it does not fit in `core`.
+	fn buffer(start: u16) -> Option<core::ops::Range<usize>> {
+		let end = start as usize + BLOCK_SIZE / 2;
+		if end > CORE_SIZE { return None }
+		Some((start as usize)..end)
+	}
+
+	/// `read_block` is for internal use only, it reads `block` from `store`
+	/// into the buffer starting at cell `start`, returning zero on success
+	/// and all bits set on an error, when memory is left as it was. Blocks
+	/// hold characters, which are packed two to a cell with the first in the
+	/// low byte, so they are converted in little endian order whatever the
+	/// byte order of the image.
+	fn read_block<F: FnMut(u16)>(store: &mut dyn Store, m: &mut [u16; CORE_SIZE], start: u16, block: u16, written: &mut F) -> u16 {
+		let range = match VM::buffer(start) { Some(range) => range, None => return 0xffff };
+		let mut bytes = [0; BLOCK_SIZE];
+		if !store.read_block(block, &mut bytes) { return 0xffff }
+		for (a, b) in range.zip(bytes.chunks_exact(2)) {
+			m[a] = ByteOrder::Little.cell(b);
+			written(a as u16);
 		}
+		0
 	}
 
-	fn save_block(&self, block: &mut dyn Write, start: u16, length: u16) -> Option<u16> {
-		if ((start as u32) + (length as u32)) > 0xffff { return None }
+	/// `write_block` is for internal use only, it writes the buffer starting
+	/// at cell `start` to `block` in `store`, like `read_block`.
+	fn write_block(store: &mut dyn Store, m: &[u16], start: u16, block: u16) -> u16 {
+		let range = match VM::buffer(start) { Some(range) => range, None => return 0xffff };
+		let mut bytes = [0; BLOCK_SIZE];
+		for (b, &c) in bytes.chunks_exact_mut(2).zip(&m[range]) {
+			b.copy_from_slice(&ByteOrder::Little.bytes(c));
+		}
+		if store.write_block(block, &bytes) { 0 } else { 0xffff }
+	}
 
-		for i in start..length {
-			let lo =  self.core[i as usize] as u8;
-			let hi = (self.core[i as usize] >> 8) as u8;
-			let u: [u8; 2] = [lo, hi];
-			if let Err(r) = block.write(&u) {
-				let _ignore = r;
-				return None;
-			}
+	/// `save_section` saves `length` cells from `start` to a sink, in the
+	/// same way as the save instruction, failing without writing anything
+	/// if the section runs past the end of `core`.
+	///
+	/// # Arguments
+	///
+	/// * `output` - Output sink to write to
+	/// * `start`  - First cell to save
+	/// * `length` - Number of cells to save
+	///
+	/// # Example
+	///
+	/// ```
+	/// let vm = embed::VM::new();
+	/// let mut header = Vec::new();
+	/// vm.save_section(&mut header, 0, 11).unwrap();
+	/// assert_eq!(header.len(), 22);
+	/// ```
+	#[cfg(feature = "std")]
+	pub fn save_section(&self, output: &mut dyn Write, start: u16, length: u16) -> Option<u16> {
+		for &c in &self.core[VM::section(start, length)?] {
+			output.write_all(&self.order.bytes(c)).ok()?;
 		}
 		Some(0)
 	}
@@ -337,67 +650,166 @@ impl VM {
 	/// ```
 	///
 	/// TODO: Replace Option with proper Result return value
+	#[cfg(feature = "std")]
 	pub fn save(&self, output: &mut dyn Write) -> Option<u16> {
-		self.save_block(output, 0, CORE_SIZE as u16)
+		self.save_section(output, 0, CORE_SIZE as u16)
 	}
 
 	/// `load` the virtual machine from a source, this also reinitializes
-	/// the VM registers to their default values
+	/// the VM registers to their default values. The image header is
+	/// validated before anything is copied into `core`, so a failed load
+	/// leaves the VM untouched. The byte order of the image is detected from
+	/// its header, falling back to the one set with `set_byte_order`, and is
+	/// remembered so that saving the image writes it back out unchanged. An
+	/// image with an odd number of bytes, or one larger than memory, is
+	/// rejected rather than being cut short.
 	///
 	/// # Arguments
 	///
 	/// * `input` - Input source to read from containing core code
 	///
+	/// # Returns
+	///
+	/// The number of cells loaded, or an `ImageError` if the image is
+	/// invalid or could not be read.
+	///
 	/// # Example
 	///
-	/// ```
+	/// ```no_run
 	/// use std::fs::File;
 	/// use std::path::Path;
 	/// let mut vm = embed::VM::new();
 	/// let mut input = File::open(&Path::new("vm.blk")).unwrap();
-	/// vm.load(&mut input);
+	/// vm.load(&mut input).unwrap();
 	/// ```
 	///
-	/// TODO: Replace Option with proper Result return value
-	pub fn load(&mut self, input: &mut dyn Read) -> Option<u16> {
-		let mut i = 0 as u16;
+	#[cfg(feature = "std")]
+	pub fn load(&mut self, input: &mut dyn Read) -> Result<u16, ImageError> {
+		let mut bytes = Vec::new();
+		input.take((CORE_SIZE * 2 + 1) as u64).read_to_end(&mut bytes)?;
+		self.load_bytes(&bytes)
+	}
+
+	/// `save_as` saves the virtual machine core, like `save`, but in any of
+	/// the supported image file formats.
+	///
+	/// # Arguments
+	///
+	/// * `output` - Output sink to write to
+	/// * `format` - Image format to write
+	///
+	/// # Example
+	///
+	/// ```
+	/// let vm = embed::VM::new();
+	/// let mut hex = Vec::new();
+	/// vm.save_as(&mut hex, embed::Format::IntelHex).unwrap();
+	/// assert!(hex.starts_with(b":10000000"));
+	/// ```
+	#[cfg(feature = "std")]
+	pub fn save_as(&self, output: &mut dyn Write, format: Format) -> Result<(), ImageError> {
+		let bytes: Vec<u8> = self.core.iter().flat_map(|&c| self.order.bytes(c)).collect();
+		match format {
+			Format::Raw => { output.write_all(&bytes)?; Ok(()) }
+			Format::IntelHex => format::write_ihex(output, &bytes),
+			Format::SRecord => format::write_srec(output, &bytes),
+			Format::Verilog => format::write_verilog(output, &self.core),
+		}
+	}
+
+	/// `load_from` loads an image, like `load`, but in any of the supported
+	/// image file formats. The addresses and checksums of all records are
+	/// verified before the image header is validated.
+	///
+	/// # Arguments
+	///
+	/// * `input`  - Input source to read from
+	/// * `format` - Image format to read
+	///
+	/// # Returns
+	///
+	/// The number of cells loaded, or an `ImageError`.
+	#[cfg(feature = "std")]
+	pub fn load_from(&mut self, input: &mut dyn Read, format: Format) -> Result<u16, ImageError> {
+		if format == Format::Raw { return self.load(input) }
+		let mut text = String::new();
+		input.read_to_string(&mut text)?;
+		match format {
+			Format::IntelHex => self.load_bytes(&format::read_ihex(&text, CORE_SIZE * 2)?),
+			Format::SRecord => self.load_bytes(&format::read_srec(&text, CORE_SIZE * 2)?),
+			_ => { let order = self.order; self.load_cells(&format::read_verilog(&text, CORE_SIZE)?, order) }
+		}
+	}
+
+	fn load_bytes(&mut self, bytes: &[u8]) -> Result<u16, ImageError> {
+		if bytes.len() > CORE_SIZE * 2 { return Err(ImageError::Large(bytes.len())) }
+		if !bytes.len().is_multiple_of(2) { return Err(ImageError::Odd(bytes.len())) }
+		let order = ByteOrder::detect(bytes).unwrap_or(self.order);
+		let cells = bytes.len() / 2;
+		let mut header = [0; image::CELLS];
+		for (h, b) in header.iter_mut().zip(bytes.chunks_exact(2)) { *h = order.cell(b) }
+		ImageHeader::parse(&header[..core::cmp::min(cells, image::CELLS)])?.check_size(cells * 2)?;
 		self.reset();
-		while i < (CORE_SIZE as u16) {
-			let lo = fgetc(input);
-			let hi = fgetc(input);
-			if lo == 0xffff || hi == 0xffff { return Some(i) }
-			self.core[i as usize] = lo | (hi << 8);
-			i += 1
-		};
-		Some(i)
+		self.order = order;
+		for (c, b) in self.core.iter_mut().zip(bytes.chunks_exact(2)) { *c = order.cell(b) }
+		Ok(cells as u16)
 	}
+
+	#[cfg(feature = "std")]
+	fn load_cells(&mut self, cells: &[u16], order: ByteOrder) -> Result<u16, ImageError> {
+		let header = ImageHeader::parse(cells)?;
+		header.check_size(cells.len() * 2)?;
+		self.reset();
+		self.order = order;
+		self.core[..cells.len()].copy_from_slice(cells);
+		Ok(cells.len() as u16)
+	}
+
+	/// `image_header` parses the header of the image currently in `core`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// let vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+	/// let header = vm.image_header().unwrap();
+	/// assert_eq!(header.version(), 0x1984);
+	/// ```
+	pub fn image_header(&self) -> Result<ImageHeader, ImageError> {
+		ImageHeader::parse(&self.core)
+	}
+}
+
+impl Default for VM {
+	fn default() -> Self { VM::new() }
 }
 
+#[cfg(test)]
+mod conformance;
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use std::cmp;
+	#[cfg(feature = "std")]
+	use proptest::prelude::*;
 
 	const BYE: u16 = 0x7b00;
 	const ADD: u16 = 0x6523;
 	const DEC: u16 = 0x6B00;
 
 	fn literal(l: u16) -> u16 {
-		if l & 0x8000 == 0x8000 { panic!("invalid literal {} > 0x7fff", format!("{}", l)) };
+		if l & 0x8000 == 0x8000 { panic!("invalid literal {} > 0x7fff", l) };
 		l | 0x8000
 	}
 
 	fn core(dst: &mut [u16], src: &[u16]) {
 		let len = cmp::min(src.len(), dst.len());
-		for i in 0..len {
-			dst[i] = src[i]
-		}	
+		dst[..len].copy_from_slice(&src[..len]);
 	}
 
 	fn expect(vm: &mut VM, val: i32, program: &[u16]) {
-		let (mut input, mut output) = (std::io::stdin(), std::io::stdout());
 		core(&mut vm.core, program);
-		assert_eq!(vm.run(None, &mut input, &mut output), val);
+		assert_eq!(vm.execute(&mut (), &mut (), &mut ()), val);
 		vm.reset();
 	}
 
@@ -409,5 +821,251 @@ mod tests {
 		expect(&mut vm, 54, &[literal(55), DEC, BYE]);
 		expect(&mut vm, 4,  &[literal(2),  literal(2), ADD, BYE]);
 	}
-}
 
+	#[test]
+	fn stack() {
+		let mut vm = VM::new();
+		assert_eq!(vm.pop(), None);
+		vm.push(2);
+		vm.push(3);
+		assert_eq!(vm.depth(), 2);
+		core(&mut vm.core, &[ADD, BYE]);
+		assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Running);
+		assert_eq!(vm.step(1, &mut (), &mut (), &mut ()), Outcome::Halted(5));
+		assert_eq!(vm.pop(), Some(5));
+		assert_eq!(vm.pop(), None);
+
+		vm.write(0x8001, 0x1234);
+		assert_eq!(vm.read(1), 0x1234);
+	}
+
+	/// `Pending` is input that arrives a chunk at a time, with nothing
+	/// available in between.
+	#[cfg(feature = "kernel")]
+	struct Pending<'a>(&'a [u8]);
+
+	#[cfg(feature = "kernel")]
+	impl<'a> Input for Pending<'a> {
+		fn getc(&mut self) -> Option<u8> { self.0.getc() }
+		fn available(&mut self) -> bool { !self.0.is_empty() }
+	}
+
+	#[test]
+	#[cfg(feature = "kernel")]
+	fn need_input() {
+		let mut vm = VM::with_image(ImageId::Kernel);
+		let mut output = Vec::new();
+		assert_eq!(vm.step(1000, &mut (), &mut Pending(b""), &mut output), Outcome::NeedInput);
+		let registers = (vm.pc, vm.rp, vm.sp, vm.t);
+		assert_eq!(vm.core[vm.pc as usize] & 0xff00, 0x7800);
+		assert_eq!(vm.step(1000, &mut (), &mut Pending(b""), &mut output), Outcome::NeedInput);
+		assert_eq!((vm.pc, vm.rp, vm.sp, vm.t), registers);
+		assert!(output.is_empty());
+
+		assert_eq!(vm.step(1000, &mut (), &mut Pending(b"hi"), &mut output), Outcome::NeedInput);
+		assert_eq!((vm.pc, vm.rp, vm.sp), (registers.0, registers.1, registers.2));
+		assert_eq!(output, b"hi");
+		assert_eq!(vm.step(1000, &mut (), &mut (), &mut output), Outcome::Halted(0));
+	}
+
+	#[test]
+	fn limits() {
+		let mut vm = VM::new();
+		core(&mut vm.core, &[DEC, 0x0000]);
+		#[allow(clippy::needless_update)] /* time is only a field with std */
+		let limits = Limits { cycles: Some(10), ..Default::default() };
+		assert_eq!(vm.run_limited(&limits, &mut (), &mut (), &mut ()), Outcome::Timeout);
+		assert_eq!((vm.pc, vm.t), (0, 0xfffb));
+		assert_eq!(vm.run_limited(&limits, &mut (), &mut (), &mut ()), Outcome::Timeout);
+		assert_eq!((vm.pc, vm.t), (0, 0xfff6));
+
+		core(&mut vm.core, &[literal(7), BYE]);
+		vm.reset();
+		assert_eq!(vm.run_limited(&Limits::default(), &mut (), &mut (), &mut ()), Outcome::Halted(7));
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn time_limit() {
+		let mut vm = VM::new();
+		core(&mut vm.core, &[0x0000]);
+		let limits = Limits { cycles: None, time: Some(Duration::from_millis(10)) };
+		let start = Instant::now();
+		assert_eq!(vm.run_limited(&limits, &mut (), &mut (), &mut ()), Outcome::Timeout);
+		assert!(start.elapsed() >= Duration::from_millis(10));
+	}
+
+	#[test]
+	#[cfg(all(feature = "std", feature = "eforth"))]
+	fn load() {
+		let mut vm = VM::new();
+		let mut image = Vec::new();
+		vm.save(&mut image).unwrap();
+		assert_eq!(vm.load(&mut &image[..]).unwrap() as usize, CORE_SIZE);
+
+		image[4] = 0;
+		assert!(matches!(vm.load(&mut &image[..]), Err(ImageError::Magic)));
+		assert!(matches!(vm.load(&mut &image[..100]), Err(ImageError::Magic)));
+		image[4] = 0x89;
+		assert!(matches!(vm.load(&mut &image[..100]), Err(ImageError::Size { .. })));
+		image.extend_from_slice(&[0, 0]);
+		assert!(matches!(vm.load(&mut &image[..]), Err(ImageError::Large(_))));
+		assert!(matches!(VM::from_bytes(&image), Err(ImageError::Large(_))));
+	}
+
+	#[test]
+	#[cfg(all(feature = "std", feature = "eforth"))]
+	fn load_big_endian() {
+		let mut vm = VM::new();
+		let mut image = Vec::new();
+		vm.set_byte_order(ByteOrder::Big);
+		vm.save(&mut image).unwrap();
+		assert_eq!(&image[4..8], &[0x46, 0x89, 0x48, 0x54]);
+
+		let mut vm = VM::new();
+		assert_eq!(vm.load(&mut &image[..]).unwrap() as usize, CORE_SIZE);
+		assert_eq!(vm.byte_order(), ByteOrder::Big);
+		assert_eq!(vm.core[..], VM::new().core[..]);
+
+		let mut saved = Vec::new();
+		vm.save(&mut saved).unwrap();
+		assert_eq!(image, saved);
+	}
+
+	#[test]
+	#[cfg(all(feature = "kernel", feature = "test-image"))]
+	fn images() {
+		let mut output = Vec::new();
+		let mut vm = VM::with_image(ImageId::Test);
+		assert_eq!(vm.run(None, &mut &b""[..], &mut output), 42);
+		assert_eq!(output, b"ok\n");
+
+		output.clear();
+		let mut vm = VM::with_image(ImageId::Kernel);
+		assert_eq!(vm.run(None, &mut &b"echo"[..], &mut output), 0);
+		assert_eq!(output, b"echo");
+		assert_eq!(vm.image_header().unwrap().length(), 38);
+	}
+
+	#[test]
+	#[cfg(all(feature = "std", feature = "eforth"))]
+	fn formats() {
+		for &format in &[Format::Raw, Format::IntelHex, Format::SRecord, Format::Verilog] {
+			let mut vm = VM::new();
+			vm.set_byte_order(ByteOrder::Big);
+			let mut image = Vec::new();
+			vm.save_as(&mut image, format).unwrap();
+			let mut vm = VM::new();
+			vm.core[CORE_SIZE - 1] = 1;
+			assert_eq!(vm.load_from(&mut &image[..], format).unwrap() as usize, CORE_SIZE);
+			assert_eq!(vm.core[..], VM::new().core[..]);
+		}
+	}
+
+	/// `Saved` records what the save instruction stores, in order.
+	#[cfg(feature = "std")]
+	struct Saved(Vec<u8>);
+
+	#[cfg(feature = "std")]
+	impl Store for Saved {
+		fn store(&mut self, cells: &[u16], order: ByteOrder) -> bool {
+			self.0.extend(cells.iter().flat_map(|&c| order.bytes(c)));
+			true
+		}
+	}
+
+	/// `random` makes a virtual machine with `cells` at the start of its core
+	/// and a valid header, which claims only to be a header.
+	#[cfg(feature = "std")]
+	fn random(cells: &[u16], order: ByteOrder) -> VM {
+		let mut vm = VM::blank();
+		core(&mut vm.core, cells);
+		vm.core[2..4].copy_from_slice(&image::MAGIC);
+		vm.core[4..6].copy_from_slice(&image::LINE_ENDINGS);
+		vm.core[6] = (image::CELLS * 2) as u16;
+		vm.core[8] = image::ENDIAN;
+		vm.core[9] = image::VERSION;
+		vm.set_byte_order(order);
+		vm
+	}
+
+	#[cfg(feature = "std")]
+	fn order() -> impl Strategy<Value = ByteOrder> {
+		prop_oneof![Just(ByteOrder::Little), Just(ByteOrder::Big)]
+	}
+
+	#[cfg(feature = "std")]
+	proptest! {
+		#![proptest_config(ProptestConfig::with_cases(64))]
+
+		#[test]
+		fn save_load(cells in prop::collection::vec(any::<u16>(), 0..=CORE_SIZE), order in order()) {
+			let vm = random(&cells, order);
+			let mut image = Vec::new();
+			prop_assert_eq!(vm.save(&mut image), Some(0));
+			prop_assert_eq!(image.len(), CORE_SIZE * 2);
+
+			let mut loaded = VM::new();
+			prop_assert_eq!(loaded.load(&mut &image[..]).unwrap() as usize, CORE_SIZE);
+			prop_assert!(loaded.core[..] == vm.core[..]);
+			prop_assert_eq!(loaded.byte_order(), order);
+		}
+
+		#[test]
+		fn save_load_section(cells in prop::collection::vec(any::<u16>(), 0..=CORE_SIZE), end in image::CELLS..=CORE_SIZE, order in order()) {
+			let vm = random(&cells, order);
+			let mut image = Vec::new();
+			prop_assert_eq!(vm.save_section(&mut image, 0, end as u16), Some(0));
+			prop_assert_eq!(image.len(), end * 2);
+
+			let loaded = VM::from_bytes(&image).unwrap();
+			prop_assert!(loaded.core[..end] == vm.core[..end]);
+			prop_assert!(loaded.core[end..].iter().all(|&c| c == 0));
+		}
+
+		#[test]
+		fn save_partial_section(cells in prop::collection::vec(any::<u16>(), 0..=CORE_SIZE), start in 0..CORE_SIZE, length in 0..=CORE_SIZE, order in order()) {
+			let vm = random(&cells, order);
+			let length = length.min(CORE_SIZE - start);
+			let mut section = Vec::new();
+			prop_assert_eq!(vm.save_section(&mut section, start as u16, length as u16), Some(0));
+			prop_assert_eq!(section.len(), length * 2);
+			prop_assert!(section.chunks_exact(2).map(|b| order.cell(b)).eq(vm.core[start..start + length].iter().cloned()));
+			prop_assert_eq!(vm.save_section(&mut Vec::new(), start as u16, (CORE_SIZE - start + 1) as u16), None);
+		}
+
+		#[test]
+		fn save_instruction(cells in prop::collection::vec(any::<u16>(), 0..=CORE_SIZE), n in any::<u16>(), t in any::<u16>(), order in order()) {
+			let mut vm = random(&cells, order);
+			vm.set_registers(Registers { pc: 0, rp: RP0, sp: SP0, t });
+			vm.core[0] = 0x7600;
+			vm.core[SP0 as usize] = n;
+			let mut saved = Saved(Vec::new());
+			prop_assert_eq!(vm.step(1, &mut saved, &mut (), &mut ()), Outcome::Running);
+
+			let (start, length) = (n >> 1, ((t as u32 + 1) >> 1) as u16);
+			let mut image = Vec::new();
+			if start as usize + length as usize > CORE_SIZE {
+				prop_assert_eq!(vm.t, 0xffff);
+				prop_assert_eq!(vm.save_section(&mut image, start, length), None);
+				prop_assert!(saved.0.is_empty());
+			} else {
+				prop_assert_eq!(vm.t, 0);
+				prop_assert_eq!(vm.save_section(&mut image, start, length), Some(0));
+				prop_assert_eq!(image.len(), length as usize * 2);
+				prop_assert_eq!(saved.0, image);
+			}
+		}
+
+		#[test]
+		fn load_odd(cells in prop::collection::vec(any::<u16>(), 0..=CORE_SIZE), length in 0..CORE_SIZE) {
+			let vm = random(&cells, ByteOrder::Little);
+			let mut image = Vec::new();
+			vm.save(&mut image).unwrap();
+			let length = length * 2 + 1;
+			let mut loaded = VM::new();
+			prop_assert!(matches!(loaded.load(&mut &image[..length]), Err(ImageError::Odd(l)) if l == length));
+			prop_assert!(loaded.core[..] == VM::new().core[..]);
+		}
+	}
+}
@@ -0,0 +1,267 @@
+//! # Image File Formats
+//!
+//! Besides the raw binary format used by the C virtual machine, images can
+//! be converted to and from the formats expected by device programmers and
+//! HDL simulators, for running images on hardware implementations of the CPU.
+//! Intel HEX and Motorola S-records contain the same bytes as a raw image,
+//! whereas a Verilog memory file contains one cell per line and so is
+//! independent of byte order.
+
+use std::io::Write;
+use image::ImageError;
+
+/// `Format` is an image file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	/// Raw binary, as produced by `VM::save`
+	Raw,
+	/// Intel HEX records
+	IntelHex,
+	/// Motorola S-records
+	SRecord,
+	/// Verilog memory file, for use with `$readmemh`
+	Verilog,
+}
+
+/// `RECORD` is the number of data bytes written per Intel HEX or S-record line
+const RECORD: usize = 16;
+
+impl Format {
+	/// `from_extension` guesses a format from a file name extension, such as
+	/// "hex" or "srec".
+	pub fn from_extension(extension: &str) -> Option<Format> {
+		match extension.to_ascii_lowercase().as_str() {
+			"blk" | "bin" => Some(Format::Raw),
+			"hex" | "ihx" => Some(Format::IntelHex),
+			"srec" | "s19" | "mot" => Some(Format::SRecord),
+			"mem" | "vmem" => Some(Format::Verilog),
+			_ => None,
+		}
+	}
+}
+
+/// `write_ihex` writes `bytes` out as Intel HEX data records, starting at
+/// address zero, followed by an end of file record.
+pub fn write_ihex(output: &mut dyn Write, bytes: &[u8]) -> Result<(), ImageError> {
+	for (i, chunk) in bytes.chunks(RECORD).enumerate() {
+		let address = i * RECORD;
+		let mut record = vec![chunk.len() as u8, (address >> 8) as u8, address as u8, 0x00];
+		record.extend_from_slice(chunk);
+		writeln!(output, ":{}", hex(&record, ihex_checksum(&record)))?;
+	}
+	writeln!(output, ":00000001FF")?;
+	Ok(())
+}
+
+/// `read_ihex` decodes Intel HEX records into an image. Data, end of file,
+/// extended segment and extended linear address records are understood,
+/// start address records are ignored.
+///
+/// # Returns
+///
+/// The bytes decoded, up to the highest address written to, or an error
+/// naming the line of the first malformed record, bad checksum or address
+/// that does not fit into `limit` bytes.
+pub fn read_ihex(text: &str, limit: usize) -> Result<Vec<u8>, ImageError> {
+	let mut image = Image::new(limit);
+	let mut base: usize = 0;
+	for (line, s) in lines(text) {
+		if !s.starts_with(':') { return Err(ImageError::Syntax(line)) }
+		let record = unhex(&s[1..]).ok_or(ImageError::Syntax(line))?;
+		if record.len() < 5 || record.len() != record[0] as usize + 5 { return Err(ImageError::Syntax(line)) }
+		let (body, sum) = record.split_at(record.len() - 1);
+		if ihex_checksum(body) != sum[0] { return Err(ImageError::Checksum(line)) }
+		let address = ((body[1] as usize) << 8) | body[2] as usize;
+		let data = &body[4..];
+		match body[3] {
+			0x00 => image.write(line, base.checked_add(address).ok_or(ImageError::Address(line))?, data)?,
+			0x01 => return Ok(image.finish()),
+			0x02 if data.len() == 2 => base = (((data[0] as usize) << 8) | data[1] as usize) << 4,
+			0x04 if data.len() == 2 => base = (((data[0] as usize) << 8) | data[1] as usize) << 16,
+			0x03 | 0x05 => { }
+			_ => return Err(ImageError::Syntax(line)),
+		}
+	}
+	Ok(image.finish())
+}
+
+/// `write_srec` writes `bytes` out as a header record, 16-bit address data
+/// records, a record count and a terminator.
+pub fn write_srec(output: &mut dyn Write, bytes: &[u8]) -> Result<(), ImageError> {
+	srec(output, '0', 0, &[])?;
+	let mut count = 0;
+	for (i, chunk) in bytes.chunks(RECORD).enumerate() {
+		srec(output, '1', i * RECORD, chunk)?;
+		count += 1;
+	}
+	if count <= 0xffff { srec(output, '5', count, &[])?; }
+	srec(output, '9', 0, &[])
+}
+
+fn srec(output: &mut dyn Write, kind: char, address: usize, data: &[u8]) -> Result<(), ImageError> {
+	let mut record = vec![(data.len() + 3) as u8, (address >> 8) as u8, address as u8];
+	record.extend_from_slice(data);
+	writeln!(output, "S{}{}", kind, hex(&record, srec_checksum(&record)))?;
+	Ok(())
+}
+
+/// `read_srec` decodes Motorola S-records into an image, S1, S2 and S3 data
+/// records are accepted, header, count and termination records are checked
+/// but otherwise ignored.
+///
+/// # Returns
+///
+/// See `read_ihex`.
+pub fn read_srec(text: &str, limit: usize) -> Result<Vec<u8>, ImageError> {
+	let mut image = Image::new(limit);
+	for (line, s) in lines(text) {
+		let kind = match s.strip_prefix('S') {
+			Some(rest) if !rest.is_empty() && s.is_ascii() => rest.as_bytes()[0],
+			_ => return Err(ImageError::Syntax(line)),
+		};
+		let record = unhex(&s[2..]).ok_or(ImageError::Syntax(line))?;
+		if record.len() < 2 || record.len() != record[0] as usize + 1 { return Err(ImageError::Syntax(line)) }
+		let (body, sum) = record.split_at(record.len() - 1);
+		if srec_checksum(body) != sum[0] { return Err(ImageError::Checksum(line)) }
+		let width = match kind {
+			b'0' | b'1' | b'5' | b'9' => 2,
+			b'2' | b'6' | b'8' => 3,
+			b'3' | b'7' => 4,
+			_ => return Err(ImageError::Syntax(line)),
+		};
+		if body.len() < width + 1 { return Err(ImageError::Syntax(line)) }
+		let address = body[1..=width].iter().fold(0, |a, &b| (a << 8) | b as usize);
+		match kind {
+			b'1' | b'2' | b'3' => image.write(line, address, &body[width + 1..])?,
+			b'7' | b'8' | b'9' => return Ok(image.finish()),
+			_ => { }
+		}
+	}
+	Ok(image.finish())
+}
+
+/// `write_verilog` writes `cells` out as a Verilog memory file, one cell per
+/// line, suitable for `$readmemh`.
+pub fn write_verilog(output: &mut dyn Write, cells: &[u16]) -> Result<(), ImageError> {
+	writeln!(output, "@0000")?;
+	for c in cells {
+		writeln!(output, "{:04x}", c)?;
+	}
+	Ok(())
+}
+
+/// `read_verilog` decodes a Verilog memory file, `@` address directives and
+/// `//` comments are supported.
+///
+/// # Returns
+///
+/// The cells decoded, up to the highest address written to, or an error
+/// naming the line of the first invalid word or out of range address.
+pub fn read_verilog(text: &str, limit: usize) -> Result<Vec<u16>, ImageError> {
+	let mut cells = vec![0; limit];
+	let (mut address, mut end) = (0, 0);
+	for (line, s) in lines(text) {
+		for word in s.split_whitespace() {
+			if let Some(a) = word.strip_prefix('@') {
+				address = usize::from_str_radix(a, 16).map_err(|_| ImageError::Syntax(line))?;
+				continue;
+			}
+			let c = u16::from_str_radix(word, 16).map_err(|_| ImageError::Syntax(line))?;
+			if address >= limit { return Err(ImageError::Address(line)) }
+			cells[address] = c;
+			address += 1;
+			end = end.max(address);
+		}
+	}
+	cells.truncate(end);
+	Ok(cells)
+}
+
+/// `Image` accumulates data records into a buffer of at most `limit` bytes.
+struct Image {
+	bytes: Vec<u8>,
+	end: usize,
+}
+
+impl Image {
+	fn new(limit: usize) -> Self { Image { bytes: vec![0; limit], end: 0 } }
+
+	fn write(&mut self, line: usize, address: usize, data: &[u8]) -> Result<(), ImageError> {
+		let end = address.checked_add(data.len()).filter(|&end| end <= self.bytes.len()).ok_or(ImageError::Address(line))?;
+		self.bytes[address..end].copy_from_slice(data);
+		self.end = self.end.max(end);
+		Ok(())
+	}
+
+	fn finish(mut self) -> Vec<u8> {
+		self.bytes.truncate(self.end);
+		self.bytes
+	}
+}
+
+/// `lines` yields the non-blank lines of `text`, numbered from one, with
+/// comments and surrounding white space removed.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+	text.lines().enumerate()
+		.map(|(i, l)| (i + 1, l.split("//").next().unwrap_or("").trim()))
+		.filter(|&(_, l)| !l.is_empty())
+}
+
+fn ihex_checksum(bytes: &[u8]) -> u8 {
+	bytes.iter().fold(0u8, |a, &b| a.wrapping_add(b)).wrapping_neg()
+}
+
+fn srec_checksum(bytes: &[u8]) -> u8 {
+	!bytes.iter().fold(0u8, |a, &b| a.wrapping_add(b))
+}
+
+fn hex(bytes: &[u8], checksum: u8) -> String {
+	bytes.iter().chain(Some(&checksum)).map(|b| format!("{:02X}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+	if !s.len().is_multiple_of(2) || !s.is_ascii() { return None }
+	(0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ihex() {
+		let mut out = Vec::new();
+		write_ihex(&mut out, &[0x02, 0x33, 0x7a]).unwrap();
+		let text = String::from_utf8(out).unwrap();
+		assert_eq!(text, ":0300000002337A4E\n:00000001FF\n");
+		assert_eq!(read_ihex(&text, 16).unwrap(), vec![0x02, 0x33, 0x7a]);
+		assert!(matches!(read_ihex(":0300000002337A1F\n", 16), Err(ImageError::Checksum(1))));
+		assert!(matches!(read_ihex("\n:0300100002337A3E\n", 16), Err(ImageError::Address(2))));
+		assert!(matches!(read_ihex(":03000000023\n", 16), Err(ImageError::Syntax(1))));
+		/* the highest addresses are out of range rather than overflowing */
+		assert!(matches!(read_ihex(":02000004FFFFFC\n:02FFFF000102FD\n", 16), Err(ImageError::Address(2))));
+	}
+
+	#[test]
+	fn srec() {
+		let mut out = Vec::new();
+		write_srec(&mut out, &[0x02, 0x33, 0x7a]).unwrap();
+		let text = String::from_utf8(out).unwrap();
+		assert_eq!(text, "S0030000FC\nS106000002337A4A\nS5030001FB\nS9030000FC\n");
+		assert_eq!(read_srec(&text, 16).unwrap(), vec![0x02, 0x33, 0x7a]);
+		assert!(matches!(read_srec("S106000002337A4B\n", 16), Err(ImageError::Checksum(1))));
+		assert!(matches!(read_srec("S106001002337143\n", 16), Err(ImageError::Address(1))));
+		assert!(matches!(read_srec("S306FFFFFFFF01FC\n", 16), Err(ImageError::Address(1))));
+	}
+
+	#[test]
+	fn verilog() {
+		let mut out = Vec::new();
+		write_verilog(&mut out, &[0x0952, 0x034d]).unwrap();
+		let text = String::from_utf8(out).unwrap();
+		assert_eq!(read_verilog(&text, 4).unwrap(), vec![0x0952, 0x034d]);
+		assert_eq!(read_verilog("@2 // skip\nffff", 4).unwrap(), vec![0, 0, 0xffff]);
+		assert!(matches!(read_verilog("@4\n1", 4), Err(ImageError::Address(2))));
+		assert!(matches!(read_verilog("xyz", 4), Err(ImageError::Syntax(1))));
+	}
+}
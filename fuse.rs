@@ -0,0 +1,383 @@
+//! # Superinstructions
+//!
+//! `Fused` runs a virtual machine from a cache of decoded instructions, as
+//! `decode::Predecoded` does, but pairs of instructions that often run one
+//! after another in eForth are decoded together as a single superinstruction,
+//! which saves a trip around the interpreter loop for the second of them. The
+//! pairs, which `patterns` counts in an image, are:
+//!
+//! * a call to a word that is a single ALU instruction with an exit, which
+//!   is how eForth defines primitives such as `dup` and `<`, and so by far
+//!   the most common pair run
+//! * a literal followed by an ALU instruction, such as `1 +`
+//! * two ALU instructions, such as `over +`, or an ALU instruction followed
+//!   by an exit
+//! * an ALU instruction followed by a 0branch, such as `0= if`
+//!
+//! The first instruction of a pair must carry on to the second, so ALU
+//! instructions that return, halt, read input or divide, which jumps to the
+//! trap handler on division by zero, are never first. A superinstruction is
+//! thrown away when either of its cells is written to, and if the first
+//! instruction writes over the second the second is decoded again instead.
+//! A call is fused with the word it calls as long as the cache entry for the
+//! word is unchanged, which is checked each time it is run.
+
+use decode::{Alu, Op};
+use host::{Input, Output, Store};
+use {Outcome, VM, CORE_SIZE};
+#[cfg(test)]
+use Registers;
+
+/// `Super` is a decoded instruction, or a pair of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Super {
+	/// Not decoded yet, or written to since it was
+	Undecoded,
+	/// A single instruction
+	Single(Op),
+	/// A literal followed by an ALU instruction
+	LiteralAlu(u16, Alu),
+	/// Two ALU instructions
+	AluAlu(Alu, Alu),
+	/// An ALU instruction followed by a 0branch
+	AluZeroBranch(Alu, u16),
+	/// A call to a word made of a single ALU instruction with an exit
+	CallExit(u16, Alu),
+}
+
+/// `Pattern` is the kind of a pair of instructions counted by `patterns`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Pattern {
+	/// A literal followed by an ALU instruction
+	LiteralAlu,
+	/// Two ALU instructions
+	AluAlu,
+	/// An ALU instruction followed by a 0branch
+	AluZeroBranch,
+	/// A call to a word made of a single ALU instruction with an exit
+	CallExit,
+}
+
+impl Super {
+	/// `fuse` decodes the instruction at `pc`, together with the one after it
+	/// if the two make up a superinstruction.
+	pub fn fuse(m: &[u16], pc: usize) -> Super {
+		let first = Op::decode(m[pc]);
+		if let Op::Call(target) = first {
+			return match Super::primitive(m, target) { Some(alu) => Super::CallExit(target, alu), None => Super::Single(first) };
+		}
+		let second = match m.get(pc + 1) { Some(&i) => Op::decode(i), None => return Super::Single(first) };
+		match (first, second) {
+			(Op::Literal(value), Op::Alu(alu)) if !alu.reads_input() => Super::LiteralAlu(value, alu),
+			(Op::Alu(a), Op::Alu(b)) if Super::first(a) && !b.reads_input() => Super::AluAlu(a, b),
+			(Op::Alu(alu), Op::ZeroBranch(target)) if Super::first(alu) => Super::AluZeroBranch(alu, target),
+			_ => Super::Single(first),
+		}
+	}
+
+	/// `primitive` returns the ALU instruction the word at `target` is made
+	/// of, if it is a single ALU instruction with an exit.
+	fn primitive(m: &[u16], target: u16) -> Option<Alu> {
+		match m.get(target as usize).map(|&i| Op::decode(i)) {
+			Some(Op::Alu(alu)) if alu.flags & Alu::R_TO_PC != 0 && !alu.reads_input() => Some(alu),
+			_ => None,
+		}
+	}
+
+	/// `first` is true if `alu` always carries on to the next instruction.
+	fn first(alu: Alu) -> bool {
+		alu.flags & Alu::R_TO_PC == 0 && !matches!(alu.op, 24..=27)
+	}
+
+	/// `head` returns the first instruction.
+	pub fn head(self) -> Op {
+		match self {
+			Super::Undecoded => Op::Undecoded,
+			Super::Single(op) => op,
+			Super::LiteralAlu(value, _) => Op::Literal(value),
+			Super::AluAlu(alu, _) | Super::AluZeroBranch(alu, _) => Op::Alu(alu),
+			Super::CallExit(target, _) => Op::Call(target),
+		}
+	}
+
+	/// `tail` returns the second instruction, if there is one.
+	pub fn tail(self) -> Option<Op> {
+		match self {
+			Super::Undecoded | Super::Single(_) => None,
+			Super::LiteralAlu(_, alu) | Super::AluAlu(_, alu) | Super::CallExit(_, alu) => Some(Op::Alu(alu)),
+			Super::AluZeroBranch(_, target) => Some(Op::ZeroBranch(target)),
+		}
+	}
+}
+
+/// `patterns` counts the pairs of instructions of each `Pattern` in `cells`,
+/// most common first. Data is counted as well as code, as the two cannot be
+/// told apart, and how often each pair is run is not known, so the counts
+/// are only a guide. The eForth kernel inlines its primitives, so calls to
+/// them are rare in the image, but words it compiles call them instead.
+///
+/// # Example
+///
+/// ```
+/// use embed::fuse::{patterns, Pattern};
+/// assert_eq!(patterns(&[0x8001, 0x6203, 0x4003, 0x601c]), [(Pattern::LiteralAlu, 1), (Pattern::CallExit, 1)]);
+/// ```
+pub fn patterns(cells: &[u16]) -> Vec<(Pattern, usize)> {
+	let mut counts = [(Pattern::LiteralAlu, 0), (Pattern::AluAlu, 0), (Pattern::AluZeroBranch, 0), (Pattern::CallExit, 0)];
+	for pair in cells.windows(2) {
+		let pattern = match (Op::decode(pair[0]), Op::decode(pair[1])) {
+			(Op::Call(target), _) if Super::primitive(cells, target).is_some() => Pattern::CallExit,
+			(Op::Literal(_), Op::Alu(_)) => Pattern::LiteralAlu,
+			(Op::Alu(a), Op::Alu(_)) if a.flags & Alu::R_TO_PC == 0 => Pattern::AluAlu,
+			(Op::Alu(a), Op::ZeroBranch(_)) if a.flags & Alu::R_TO_PC == 0 => Pattern::AluZeroBranch,
+			_ => continue,
+		};
+		counts[pattern as usize].1 += 1;
+	}
+	let mut counts: Vec<_> = counts.iter().cloned().filter(|c| c.1 > 0).collect();
+	counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+	counts
+}
+
+/// `Fused` runs a virtual machine from a cache of superinstructions, it
+/// behaves exactly as running the virtual machine with `VM::step` does.
+/// Memory has to be written through `write`, or `vm_mut`, for the cache to
+/// be kept up to date.
+///
+/// # Example
+///
+/// ```
+/// let vm = embed::VM::from_bytes(include_bytes!("eforth.blk")).unwrap();
+/// let mut fast = embed::fuse::Fused::new(vm);
+/// let mut input: &[u8] = b"2 3 + . 7 (bye)\n";
+/// assert_eq!(fast.execute(&mut (), &mut input, &mut ()), 7);
+/// ```
+pub struct Fused {
+	vm: VM,
+	ops: Box<[Super]>,
+}
+
+impl Fused {
+	/// `new` decodes all of the memory of `vm`.
+	pub fn new(vm: VM) -> Self {
+		let ops = (0..CORE_SIZE).map(|pc| Super::fuse(&vm.core, pc)).collect();
+		Fused { vm, ops }
+	}
+
+	/// `vm` returns the virtual machine.
+	pub fn vm(&self) -> &VM { &self.vm }
+
+	/// `vm_mut` returns the virtual machine for modification, which throws
+	/// away all of the decoded instructions.
+	pub fn vm_mut(&mut self) -> &mut VM {
+		for op in self.ops.iter_mut() { *op = Super::Undecoded }
+		&mut self.vm
+	}
+
+	/// `into_inner` returns the virtual machine.
+	pub fn into_inner(self) -> VM { self.vm }
+
+	/// `read` returns the cell at `address`, as `VM::read` does.
+	pub fn read(&self, address: u16) -> u16 { self.vm.read(address) }
+
+	/// `write` sets the cell at `address`, as `VM::write` does.
+	pub fn write(&mut self, address: u16, value: u16) {
+		self.vm.write(address, value);
+		Fused::invalidate(&mut self.ops, address);
+	}
+
+	/// `invalidate` throws away the superinstructions that include the cell
+	/// at `address`.
+	fn invalidate(ops: &mut [Super], address: u16) {
+		let address = address as usize % CORE_SIZE;
+		ops[address] = Super::Undecoded;
+		if address > 0 { ops[address - 1] = Super::Undecoded }
+	}
+
+	/// `execute` runs until the virtual machine halts, as `VM::execute`.
+	pub fn execute(&mut self, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> i32 {
+		loop {
+			if let Outcome::Halted(r) = self.step(u64::MAX, store, input, output) { return r }
+		}
+	}
+
+	/// `step` executes at most `cycles` instructions, as `VM::step`, each
+	/// instruction of a superinstruction counts as one.
+	pub fn step(&mut self, cycles: u64, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> Outcome {
+		let vm = &mut self.vm;
+		let ops = &mut self.ops;
+		let mut r = vm.registers();
+		let (order, tracing, mut count) = (vm.order, vm.tracing, vm.count);
+		let (mut cycles, mut outcome) = (cycles, Outcome::Running);
+
+		if tracing && count == 0 { VM::header(&mut std::io::stderr()) }
+
+		let m = &mut vm.core;
+		/* every arm has its own copy of `exec`, specialised for the instructions it runs */
+		macro_rules! exec {
+			($op:expr) => {
+				if VM::exec($op, &mut r, m, order, store, input, output, &mut |a| Fused::invalidate(ops, a)) {
+					outcome = Outcome::Halted((r.t as i16) as i32);
+					break;
+				}
+			};
+		}
+		/* the tail is skipped if the head wrote over it, which also throws away the pair */
+		macro_rules! tail {
+			($pc:expr, $op:expr) => {
+				if matches!(ops[$pc], Super::Undecoded) { continue }
+				cycles -= 1;
+				exec!($op);
+			};
+		}
+		while cycles > 0 {
+			let pc = r.pc as usize;
+			let mut op = ops[pc];
+			if matches!(op, Super::Undecoded) {
+				op = Super::fuse(&m[..], pc);
+				ops[pc] = op;
+			}
+			/* getc is never part of a pair */
+			if let Super::Single(Op::Alu(Alu { op: 24, .. })) = op {
+				if !input.available() { /* input, suspend until available */
+					outcome = Outcome::NeedInput;
+					break;
+				}
+			}
+			cycles -= 1;
+
+			if tracing {
+				VM::csv(&mut std::io::stderr(), &mut count, r.pc, m[pc], r.t, r.sp, r.rp);
+				exec!(op.head()); /* one instruction at a time, so each is logged */
+				continue;
+			}
+
+			match op {
+				Super::Single(Op::Literal(value)) => exec!(Op::Literal(value)),
+				Super::Single(Op::Alu(alu)) => exec!(Op::Alu(alu)),
+				Super::Single(Op::Call(target)) => exec!(Op::Call(target)),
+				Super::Single(Op::ZeroBranch(target)) => exec!(Op::ZeroBranch(target)),
+				Super::Single(Op::Branch(target)) => exec!(Op::Branch(target)),
+				Super::LiteralAlu(value, alu) => {
+					exec!(Op::Literal(value));
+					if cycles == 0 { break }
+					tail!(pc, Op::Alu(alu));
+				}
+				Super::AluAlu(a, b) => {
+					exec!(Op::Alu(a));
+					if cycles == 0 { break }
+					tail!(pc, Op::Alu(b));
+				}
+				Super::AluZeroBranch(alu, target) => {
+					exec!(Op::Alu(alu));
+					if cycles == 0 { break }
+					tail!(pc, Op::ZeroBranch(target));
+				}
+				Super::CallExit(target, alu) => {
+					exec!(Op::Call(target));
+					if cycles == 0 { break }
+					if !matches!(ops[target as usize], Super::Single(Op::Alu(a)) if a == alu) { continue } /* the word has changed, or is not decoded yet */
+					cycles -= 1;
+					exec!(Op::Alu(alu));
+				}
+				Super::Undecoded | Super::Single(Op::Undecoded) => unreachable!("executed an undecoded instruction"),
+			}
+		}
+
+		vm.set_registers(r);
+		vm.count = count;
+		outcome
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fuse() {
+		let m = [0x8001, 0x6203, 0x6101, 0x6f03, 0x600c, 0x2000, 0x7b00, 0x6000, 0x601c, 0x6000, 0x7881];
+		assert_eq!(Super::fuse(&m, 0), Super::LiteralAlu(1, Alu { op: 2, flags: 0, dstack: 0xffff, rstack: 0 }));
+		assert!(matches!(Super::fuse(&m, 1), Super::AluAlu(..)));
+		assert!(matches!(Super::fuse(&m, 4), Super::AluZeroBranch(_, 0)));
+		assert_eq!(Super::fuse(&m, 6), Super::Single(Op::decode(0x7b00))); /* halt is never first */
+		assert!(matches!(Super::fuse(&m, 7), Super::AluAlu(_, Alu { flags: Alu::R_TO_PC, .. })));
+		assert_eq!(Super::fuse(&m, 8), Super::Single(Op::decode(0x601c))); /* nor is an exit */
+		assert_eq!(Super::fuse(&m, 9), Super::Single(Op::decode(0x6000))); /* reading input is never second */
+		assert_eq!(Super::fuse(&m, 10), Super::Single(Op::decode(0x7881)));
+
+		let m = [0x4003, 0x4002, 0x7b00, 0x6a1c /* invert exit */];
+		assert_eq!(Super::fuse(&m, 0), Super::CallExit(3, Alu { op: 10, flags: Alu::R_TO_PC, dstack: 0, rstack: 0xffff }));
+		assert_eq!(Super::fuse(&m, 1), Super::Single(Op::Call(2)));
+	}
+
+	#[test]
+	#[cfg(feature = "eforth")]
+	fn hot() {
+		/* counts depend on how the image was built, only how they rank is checked */
+		let mut vm = VM::new();
+		let found = patterns(::ImageId::Eforth.cells());
+		assert_eq!(found[0].0, Pattern::AluAlu);
+		assert!(found[0].1 >= 100, "{:?}", found);
+		assert!(found.iter().any(|&p| p.0 == Pattern::CallExit), "{:?}", found);
+
+		/* the kernel has its primitives inlined, but words compiled by eForth call them */
+		let source = b": fib dup 2 < if exit then dup 1- recurse swap 2 - recurse + ; 0 (bye)\n";
+		vm.execute(&mut (), &mut &source[..], &mut ());
+		let found = patterns(&vm.core[..]);
+		assert!(found.iter().any(|&p| p.0 == Pattern::CallExit && p.1 >= 7), "{:?}", found);
+	}
+
+	#[test]
+	#[cfg(feature = "eforth")]
+	fn differential() {
+		let source = b"decimal : fib dup 2 < if exit then dup 1- recurse swap 2 - recurse + ;\n\
+			15 fib . words\n7 0 /mod . .\n$8007 ' fib cell+ ! 5 fib . 9 (bye)\n";
+		let mut plain = VM::new();
+		let mut output = Vec::new();
+		let code = plain.execute(&mut (), &mut &source[..], &mut output);
+
+		let mut fused = Fused::new(VM::new());
+		let mut superinstructions = Vec::new();
+		assert_eq!(fused.execute(&mut (), &mut &source[..], &mut superinstructions), code);
+		assert_eq!(code, 9, "{}", String::from_utf8_lossy(&output));
+		assert_eq!(superinstructions, output);
+		assert_eq!(fused.vm().core[..], plain.core[..]);
+		assert_eq!(fused.vm().registers(), plain.registers());
+
+		/* the same in steps of one, two and three cycles, so pairs are split */
+		let (mut plain, mut fused) = (VM::new(), Fused::new(VM::new()));
+		let (mut a, mut b) = (&source[..], &source[..]);
+		for i in 0.. {
+			let cycles = i % 3 + 1;
+			let outcome = plain.step(cycles, &mut (), &mut a, &mut ());
+			assert_eq!(fused.step(cycles, &mut (), &mut b, &mut ()), outcome);
+			assert_eq!(fused.vm().registers(), plain.registers());
+			if outcome == Outcome::Halted(9) { break }
+		}
+		assert_eq!(fused.vm().core[..], plain.core[..]);
+	}
+
+	#[test]
+	fn overwrite() {
+		/* the literal pushes the top of stack over the second half of its pair */
+		let image = [0x802a, 0x6b00 /* 1- */, 0x7b00 /* bye */];
+		let mut fused = Fused::new(VM::blank());
+		for (i, &cell) in image.iter().enumerate() { fused.write(i as u16, cell) }
+		fused.vm_mut().set_registers(Registers { pc: 0, rp: 0x7fff, sp: 0, t: 0x6000 });
+		assert_eq!(fused.execute(&mut (), &mut (), &mut ()), 42);
+		assert_eq!(fused.read(1), 0x6000);
+
+		/* the call pushes its return address over the word it calls */
+		let image = [0x4005, 0x7b00, 0x7b00, 0, 0, 0x6a1c /* invert exit */];
+		let mut fused = Fused::new(VM::blank());
+		for (i, &cell) in image.iter().enumerate() { fused.write(i as u16, cell) }
+		fused.vm_mut().set_registers(Registers { pc: 0, rp: 6, sp: 0x2200, t: 42 });
+		assert_eq!(fused.execute(&mut (), &mut (), &mut ()), 42);
+		assert_eq!(fused.read(5), 2);
+
+		fused.vm_mut().set_registers(Registers { pc: 0, rp: 0x7fff, sp: 0x2200, t: 42 });
+		fused.write(5, 0x6a1c);
+		assert_eq!(fused.execute(&mut (), &mut (), &mut ()), !42);
+	}
+}
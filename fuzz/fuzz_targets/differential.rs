@@ -0,0 +1,21 @@
+//! Runs random images on the virtual machine and the reference model side
+//! by side, see `embed::reference`. The first byte of the data is the length
+//! of the input given to the image, which comes next, and the rest of the
+//! data is the image, little endian, loaded at the start of memory.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate embed;
+
+use embed::reference::compare;
+
+/// `STEPS` is the most instructions executed for each image.
+const STEPS: usize = 10_000;
+
+fuzz_target!(|data: &[u8]| {
+	let (&length, data) = match data.split_first() { Some(d) => d, None => return };
+	let (input, image) = data.split_at((length as usize).min(data.len()));
+	let image: Vec<u16> = image.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+	if let Err(d) = compare(&image, input, STEPS) { panic!("{:x?}", d) }
+});
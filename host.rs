@@ -0,0 +1,135 @@
+//! # Host Interface
+//!
+//! The virtual machine talks to the outside world through three traits, one
+//! for each kind of instruction that performs I/O: `Input` for reading a
+//! character, `Output` for writing one and `Store` for saving a section of
+//! memory and for reading and writing blocks. None of them require the
+//! standard library, so the same virtual machine can run on a
+//! microcontroller. With the `std` feature enabled `VM::run` provides
+//! implementations for any `Read` and `Write` stream, and for saving to a
+//! named file, and the `block` module provides block storage.
+
+use image::ByteOrder;
+#[cfg(feature = "std")]
+use std::io::prelude::*;
+
+/// `Input` supplies characters to the virtual machine.
+pub trait Input {
+	/// `getc` returns the next character, or `None` on end of input or
+	/// on an error.
+	fn getc(&mut self) -> Option<u8>;
+
+	/// `available` returns false if `getc` would have to wait for input,
+	/// in which case the virtual machine stops before the instruction that
+	/// reads input, so it can be resumed once there is some. The default
+	/// is for input to always be available, so `getc` blocks.
+	fn available(&mut self) -> bool { true }
+}
+
+/// `Output` consumes characters written by the virtual machine.
+pub trait Output {
+	/// `putc` writes a single character, returning false on an error.
+	fn putc(&mut self, c: u8) -> bool;
+}
+
+/// `BLOCK_SIZE` is the number of bytes in a block, the unit the block
+/// instructions read and write, 1 KiB as in Forth.
+pub const BLOCK_SIZE: usize = 1024;
+
+/// `Store` persists sections of memory written out by the save instruction,
+/// and the blocks read and written by the block instructions.
+pub trait Store {
+	/// `store` saves `cells`, which should be written out in `order` if they
+	/// are to be turned into an image, returning false on an error.
+	fn store(&mut self, cells: &[u16], order: ByteOrder) -> bool;
+
+	/// `read_block` fills `buffer` with the contents of block number
+	/// `block`, returning false if there is no such block or on an error.
+	/// The default has no blocks at all.
+	fn read_block(&mut self, _block: u16, _buffer: &mut [u8; BLOCK_SIZE]) -> bool { false }
+
+	/// `write_block` replaces the contents of block number `block` with
+	/// `buffer`, returning false if there is no such block or on an error.
+	fn write_block(&mut self, _block: u16, _buffer: &[u8; BLOCK_SIZE]) -> bool { false }
+}
+
+/// An empty input, which is always at its end.
+impl Input for () {
+	fn getc(&mut self) -> Option<u8> { None }
+}
+
+/// Discards all output.
+impl Output for () {
+	fn putc(&mut self, _c: u8) -> bool { true }
+}
+
+/// Refuses to save anything, as when `VM::run` is not given a file name, and
+/// has no blocks.
+impl Store for () {
+	fn store(&mut self, _cells: &[u16], _order: ByteOrder) -> bool { false }
+}
+
+/// Takes input from the front of the queue, it is only available while the
+/// queue is not empty, so the virtual machine waits for more to be added
+/// rather than reaching the end of its input.
+#[cfg(any(feature = "std", test))]
+impl Input for std::collections::VecDeque<u8> {
+	fn getc(&mut self) -> Option<u8> { self.pop_front() }
+	fn available(&mut self) -> bool { !self.is_empty() }
+}
+
+/// Appends all output to the vector.
+#[cfg(any(feature = "std", test))]
+impl Output for Vec<u8> {
+	fn putc(&mut self, c: u8) -> bool { self.push(c); true }
+}
+
+impl Input for &[u8] {
+	fn getc(&mut self) -> Option<u8> {
+		let (&c, rest) = self.split_first()?;
+		*self = rest;
+		Some(c)
+	}
+}
+
+/// `Reader` adapts a `Read` stream to `Input`, errors are treated as the
+/// end of input.
+#[cfg(feature = "std")]
+pub(crate) struct Reader<'a>(pub &'a mut dyn Read);
+
+/// `Writer` adapts a `Write` stream to `Output`.
+#[cfg(feature = "std")]
+pub(crate) struct Writer<'a>(pub &'a mut dyn Write);
+
+/// `FileStore` saves memory to the named file, if there is one.
+#[cfg(feature = "std")]
+pub(crate) struct FileStore<'a>(pub Option<&'a str>);
+
+#[cfg(feature = "std")]
+impl<'a> Input for Reader<'a> {
+	fn getc(&mut self) -> Option<u8> {
+		let mut u: [u8; 1] = [0];
+		if 1 == self.0.read(&mut u).unwrap_or(0) { Some(u[0]) } else { None }
+	}
+}
+
+#[cfg(feature = "std")]
+impl<'a> Output for Writer<'a> {
+	fn putc(&mut self, c: u8) -> bool {
+		1 == self.0.write(&[c]).unwrap_or(0)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<'a> Store for FileStore<'a> {
+	fn store(&mut self, cells: &[u16], order: ByteOrder) -> bool {
+		let name = match self.0 { None => return false, Some(name) => name };
+
+		let mut file = match std::fs::File::create(name) {
+			Err(r) => { println!("failed to create block \"{}\": {}", name, r); return false },
+			Ok(r) => r
+		};
+
+		cells.iter().all(|&c| file.write_all(&order.bytes(c)).is_ok())
+	}
+}
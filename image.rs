@@ -0,0 +1,285 @@
+//! # Image Header
+//!
+//! Images generated by the meta-compiler in <https://github.com/howerj/embed>
+//! start with a small header, the first few cells of which are executable
+//! (the reset and exception vectors) and the rest are used to identify the
+//! image and catch common mistakes, such as transferring an image over a link
+//! that mangles line endings. The layout, in cells, is:
+//!
+//! | Cell | Contents                                       |
+//! | ---- | ---------------------------------------------- |
+//! | 0    | Reset vector, first instruction executed       |
+//! | 1    | Exception vector                               |
+//! | 2-3  | Magic number, 0x89 'F' 'T' 'H'                 |
+//! | 4-5  | Line ending guards, '\r' '\n' ^Z '\n'          |
+//! | 6    | Length of the image in bytes                   |
+//! | 7    | CRC-16 of the image                            |
+//! | 8    | Endianess check, always 0x0001                 |
+//! | 9    | Version number                                 |
+//! | 10   | Header options                                 |
+//!
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::io;
+
+/// `CELLS` is the size of the header in 16-bit cells
+pub const CELLS: usize = 11;
+/// `MAGIC` identifies an image as being one for this virtual machine
+pub const MAGIC: [u16; 2] = [0x4689, 0x4854];
+/// `LINE_ENDINGS` are a sequence of bytes likely to be corrupted by text mode
+/// file transfers, in the same vein as the PNG header
+pub const LINE_ENDINGS: [u16; 2] = [0x0a0d, 0x0a1a];
+/// `ENDIAN` is the value the endianess check cell should contain
+pub const ENDIAN: u16 = 0x0001;
+/// `VERSION` is the only image version this virtual machine can execute
+pub const VERSION: u16 = 0x1984;
+
+/// `ByteOrder` is the order in which the two bytes of a cell are stored in
+/// an image file. Images generated on little endian hosts, which is what the
+/// C version of the virtual machine produces on most machines, are the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+	/// Least significant byte first
+	#[default]
+	Little,
+	/// Most significant byte first
+	Big,
+}
+
+impl ByteOrder {
+	/// `detect` works out the byte order of an image from the raw bytes of
+	/// its header, first by looking at the magic number and then at the
+	/// endianess check cell.
+	///
+	/// # Returns
+	///
+	/// `None` if neither field can be recognized in either byte order.
+	pub fn detect(bytes: &[u8]) -> Option<ByteOrder> {
+		for &order in &[ByteOrder::Little, ByteOrder::Big] {
+			if bytes.len() >= 8 && order.cell(&bytes[4..6]) == MAGIC[0] && order.cell(&bytes[6..8]) == MAGIC[1] {
+				return Some(order)
+			}
+		}
+		for &order in &[ByteOrder::Little, ByteOrder::Big] {
+			if bytes.len() >= 18 && order.cell(&bytes[16..18]) == ENDIAN {
+				return Some(order)
+			}
+		}
+		None
+	}
+
+	/// `cell` turns the first two bytes of `b` into a cell.
+	pub fn cell(self, b: &[u8]) -> u16 {
+		match self {
+			ByteOrder::Little => (b[0] as u16) | ((b[1] as u16) << 8),
+			ByteOrder::Big    => (b[1] as u16) | ((b[0] as u16) << 8),
+		}
+	}
+
+	/// `bytes` turns a cell into two bytes to be written out.
+	pub fn bytes(self, c: u16) -> [u8; 2] {
+		match self {
+			ByteOrder::Little => c.to_le_bytes(),
+			ByteOrder::Big    => c.to_be_bytes(),
+		}
+	}
+}
+
+/// `ImageError` describes all the ways loading an image can fail.
+#[derive(Debug)]
+pub enum ImageError {
+	/// The underlying input stream returned an error
+	#[cfg(feature = "std")]
+	Io(io::Error),
+	/// The image is too small to even contain a header
+	Truncated(usize),
+	/// The magic number is wrong, this is probably not an image at all
+	Magic,
+	/// The line ending guards have been altered, most likely by a transfer
+	/// done in text mode
+	LineEndings,
+	/// The endianess check failed, the image is in the wrong byte order
+	Endianess(u16),
+	/// The image was built for a different version of the virtual machine
+	Version(u16),
+	/// The length recorded in the header does not match the data available,
+	/// both are in bytes
+	Size { expected: usize, actual: usize },
+	/// The image has an odd number of bytes, so its last cell is incomplete
+	Odd(usize),
+	/// The image, of at least this many bytes, is bigger than memory
+	Large(usize),
+	/// A line of a text image format could not be parsed
+	Syntax(usize),
+	/// A record on the given line has an incorrect checksum
+	Checksum(usize),
+	/// A record on the given line writes outside of the virtual machine memory
+	Address(usize),
+}
+
+impl fmt::Display for ImageError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			#[cfg(feature = "std")]
+			ImageError::Io(ref e) => write!(f, "I/O error: {}", e),
+			ImageError::Truncated(n) => write!(f, "image truncated, {} bytes is too short for a header", n),
+			ImageError::Magic => write!(f, "invalid magic number"),
+			ImageError::LineEndings => write!(f, "line ending guards corrupted, was the image transferred in text mode?"),
+			ImageError::Endianess(e) => write!(f, "endianess check failed, expected {:04x} got {:04x}", ENDIAN, e),
+			ImageError::Version(v) => write!(f, "unsupported version, expected {:04x} got {:04x}", VERSION, v),
+			ImageError::Size { expected, actual } => write!(f, "size mismatch, header says {} bytes but {} are available", expected, actual),
+			ImageError::Odd(n) => write!(f, "image has an odd length of {} bytes, the last cell is incomplete", n),
+			ImageError::Large(n) => write!(f, "image of {} bytes or more is too large for memory", n),
+			ImageError::Syntax(l) => write!(f, "syntax error on line {}", l),
+			ImageError::Checksum(l) => write!(f, "checksum error on line {}", l),
+			ImageError::Address(l) => write!(f, "address out of range on line {}", l),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ImageError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ImageError {
+	fn from(e: io::Error) -> Self { ImageError::Io(e) }
+}
+
+/// `ImageHeader` is a parsed and validated image header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageHeader {
+	entry: u16,
+	exception: u16,
+	length: u16,
+	crc: u16,
+	version: u16,
+	options: u16,
+}
+
+impl ImageHeader {
+	/// `parse` validates the header at the start of `cells`, the cells
+	/// should already be in the correct byte order.
+	///
+	/// # Arguments
+	///
+	/// * `cells` - Image to parse, this needs to be at least `CELLS` long
+	///
+	/// # Returns
+	///
+	/// The parsed header, or the first problem found with it.
+	pub fn parse(cells: &[u16]) -> Result<Self, ImageError> {
+		if cells.len() < CELLS { return Err(ImageError::Truncated(cells.len() * 2)) }
+		if cells[2..4] != MAGIC { return Err(ImageError::Magic) }
+		if cells[4..6] != LINE_ENDINGS { return Err(ImageError::LineEndings) }
+		if cells[8] != ENDIAN { return Err(ImageError::Endianess(cells[8])) }
+		if cells[9] != VERSION { return Err(ImageError::Version(cells[9])) }
+		let h = ImageHeader {
+			entry: cells[0], exception: cells[1],
+			length: cells[6], crc: cells[7],
+			version: cells[9], options: cells[10]
+		};
+		if (h.length as usize) < CELLS * 2 {
+			return Err(ImageError::Size { expected: h.length as usize, actual: cells.len() * 2 })
+		}
+		Ok(h)
+	}
+
+	/// `check_size` makes sure that `bytes` worth of image data is enough
+	/// to contain everything the header says the image contains.
+	pub fn check_size(&self, bytes: usize) -> Result<(), ImageError> {
+		if bytes < self.length as usize {
+			return Err(ImageError::Size { expected: self.length as usize, actual: bytes })
+		}
+		Ok(())
+	}
+
+	/// `entry` is the reset vector, the first instruction to be executed.
+	pub fn entry(&self) -> u16 { self.entry }
+
+	/// `exception` is the instruction executed on a trap, such as a division
+	/// by zero.
+	pub fn exception(&self) -> u16 { self.exception }
+
+	/// `length` is the length of the image in bytes.
+	pub fn length(&self) -> u16 { self.length }
+
+	/// `crc` is the CRC-16 stored in the header, see `crc_matches`.
+	pub fn crc(&self) -> u16 { self.crc }
+
+	/// `version` of the virtual machine the image was built for.
+	pub fn version(&self) -> u16 { self.version }
+
+	/// `options` contains the header option bits.
+	pub fn options(&self) -> u16 { self.options }
+
+	/// `crc_matches` calculates the CRC of the image in `cells` and checks it
+	/// against the one stored in the header. The CRC only covers the image as
+	/// it was generated, an image saved after it has been run will most
+	/// likely fail this check, which is why `VM::load` does not perform it.
+	pub fn crc_matches(&self, cells: &[u16]) -> bool {
+		let len = self.length as usize;
+		if cells.len() * 2 < len { return false }
+		let bytes = cells.iter().enumerate()
+			.flat_map(|(i, &c)| { let c = if i == 7 { 0 } else { c }; [c as u8, (c >> 8) as u8] })
+			.take(len);
+		crc16(bytes) == self.crc
+	}
+}
+
+/// `crc16` computes the CRC-16 (CCITT polynomial 0x1021, initial value
+/// 0xffff) used by the image header.
+pub fn crc16<I: IntoIterator<Item = u8>>(bytes: I) -> u16 {
+	bytes.into_iter().fold(0xffff, |crc, b| {
+		let mut crc = crc ^ ((b as u16) << 8);
+		for _ in 0..8 {
+			crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+		}
+		crc
+	})
+}
+
+#[cfg(all(test, feature = "eforth"))]
+mod tests {
+	use super::*;
+	use images::EFORTH_CORE;
+
+	#[test]
+	fn parse() {
+		let h = ImageHeader::parse(&EFORTH_CORE).unwrap();
+		assert_eq!(h.version(), VERSION);
+		assert_eq!(h.length() as usize, EFORTH_CORE.len() * 2);
+		assert!(h.crc_matches(&EFORTH_CORE));
+		assert!(h.check_size(EFORTH_CORE.len() * 2).is_ok());
+		assert!(h.check_size(EFORTH_CORE.len()).is_err());
+	}
+
+	#[test]
+	fn corrupt() {
+		let mut m = EFORTH_CORE;
+		m[2] = 0x4688;
+		assert!(matches!(ImageHeader::parse(&m), Err(ImageError::Magic)));
+		m = EFORTH_CORE;
+		m[5] = 0x1a0a;
+		assert!(matches!(ImageHeader::parse(&m), Err(ImageError::LineEndings)));
+		m = EFORTH_CORE;
+		m[9] = 0x2018;
+		assert!(matches!(ImageHeader::parse(&m), Err(ImageError::Version(0x2018))));
+		assert!(matches!(ImageHeader::parse(&m[0..4]), Err(ImageError::Truncated(8))));
+	}
+
+	#[test]
+	fn byte_order() {
+		let le: Vec<u8> = EFORTH_CORE.iter().flat_map(|&c| ByteOrder::Little.bytes(c)).collect();
+		let be: Vec<u8> = EFORTH_CORE.iter().flat_map(|&c| ByteOrder::Big.bytes(c)).collect();
+		assert_eq!(ByteOrder::detect(&le), Some(ByteOrder::Little));
+		assert_eq!(ByteOrder::detect(&be), Some(ByteOrder::Big));
+		assert_eq!(ByteOrder::Big.cell(&be[18..20]), VERSION);
+		let mut m = be.clone();
+		m[4] = 0;
+		assert_eq!(ByteOrder::detect(&m), Some(ByteOrder::Big));
+		assert_eq!(ByteOrder::detect(&[0; 32]), None);
+	}
+}
@@ -0,0 +1,44 @@
+//! # Bundled Images
+//!
+//! The images built into the library are generated by `build.rs`, each one
+//! is behind a cargo feature so that builds only pay for the images they use:
+//!
+//! | Feature      | Image        | Source       | Contents                       |
+//! | ------------ | ------------ | ------------ | ------------------------------ |
+//! | `eforth`     | `EFORTH_CORE`| `eforth.blk` | A full eForth interpreter      |
+//! | `kernel`     | `KERNEL_CORE`| `kernel.mem` | A minimal kernel echoing input |
+//! | `test-image` | `TEST_CORE`  | `test.mem`   | Prints "ok" and exits with 42  |
+//!
+//! The source of each image can be overridden at build time with the
+//! environment variables `EFORTH_IMAGE`, `KERNEL_IMAGE` and `TEST_IMAGE`.
+
+include!(concat!(env!("OUT_DIR"), "/images.rs"));
+
+/// `ImageId` names one of the images bundled with the library, only those
+/// enabled by cargo features are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageId {
+	/// The full eForth interpreter
+	#[cfg(feature = "eforth")]
+	Eforth,
+	/// A minimal kernel
+	#[cfg(feature = "kernel")]
+	Kernel,
+	/// A tiny image for testing
+	#[cfg(feature = "test-image")]
+	Test,
+}
+
+impl ImageId {
+	/// `cells` returns the contents of the image.
+	pub fn cells(self) -> &'static [u16] {
+		match self {
+			#[cfg(feature = "eforth")]
+			ImageId::Eforth => &EFORTH_CORE,
+			#[cfg(feature = "kernel")]
+			ImageId::Kernel => &KERNEL_CORE,
+			#[cfg(feature = "test-image")]
+			ImageId::Test => &TEST_CORE,
+		}
+	}
+}
@@ -1,24 +1,377 @@
 extern crate embed;
+#[cfg(feature = "repl")]
+extern crate rustyline;
 
-/*use std::fs::File;
+#[cfg(feature = "repl")]
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io::prelude::*;
+use std::io;
+#[cfg(feature = "repl")]
+use std::io::IsTerminal;
 use std::path::Path;
-use std::env;*/
+use std::process;
+use std::time::Duration;
+
+use embed::block::{BlockFile, WORDS};
+use embed::{Format, Input, Limits, Outcome, Output, Store, VM};
+#[cfg(feature = "repl")]
+use rustyline::completion::Completer;
+#[cfg(feature = "repl")]
+use rustyline::error::ReadlineError;
+#[cfg(feature = "repl")]
+use rustyline::highlight::Highlighter;
+#[cfg(feature = "repl")]
+use rustyline::hint::Hinter;
+#[cfg(feature = "repl")]
+use rustyline::history::DefaultHistory;
+#[cfg(feature = "repl")]
+use rustyline::validate::Validator;
+#[cfg(feature = "repl")]
+use rustyline::{Context, Editor, Helper};
+
+const USAGE: &str = "usage: eforth [-i image] [-c cycles] [-t seconds] [-b blocks.blk] [script.fth [arguments...]]";
+
+/// `TIMEOUT` is the exit code used when a limit set on the command line is
+/// reached, the same as that of the *timeout* utility.
+const TIMEOUT: i32 = 124;
+
+/// `HISTORY` is the name of the file, in the home directory, that the line
+/// history of interactive sessions is kept in.
+#[cfg(feature = "repl")]
+const HISTORY: &str = ".eforth_history";
+
+/// `WORDLISTS` are the cells the bundled eForth image keeps the latest word
+/// defined in each of its word lists in, the root, editor and forth word
+/// lists. Other images may keep them elsewhere, so completion is only
+/// offered for the bundled image.
+#[cfg_attr(not(feature = "repl"), allow(dead_code))]
+const WORDLISTS: [u16; 3] = [0x10, 0x11, 0x12];
+
+/// `words` returns the names of all the words in the dictionary of a running
+/// eForth image. Each word header starts with a link to the previous header
+/// in the same word list, the top two bits of which are flags, followed by
+/// the name as a counted string.
+#[cfg_attr(not(feature = "repl"), allow(dead_code))]
+fn words(vm: &VM) -> Vec<String> {
+	let byte = |address: u16| (vm.read(address >> 1) >> ((address & 1) * 8)) as u8;
+	let mut names = Vec::new();
+	for &list in &WORDLISTS {
+		let mut link = vm.read(list) & 0x3fff;
+		while link != 0 && names.len() < 0x1000 {
+			let length = (byte(link + 2) & 0x1f) as u16;
+			names.push((0..length).map(|i| byte(link + 3 + i) as char).collect());
+			link = vm.read(link >> 1) & 0x3fff;
+		}
+	}
+	names.sort();
+	names.dedup();
+	names
+}
+
+/// `Words` completes the word under the cursor with names from the
+/// dictionary, which are read from the image before each line is edited.
+#[cfg(feature = "repl")]
+struct Words(Vec<String>);
+
+#[cfg(feature = "repl")]
+impl Completer for Words {
+	type Candidate = String;
+
+	fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+		let start = line[..pos].rfind(|c: char| c.is_ascii_whitespace()).map_or(0, |i| i + 1);
+		let prefix = &line[start..pos];
+		Ok((start, self.0.iter().filter(|w| w.starts_with(prefix)).cloned().collect()))
+	}
+}
+
+#[cfg(feature = "repl")]
+impl Hinter for Words { type Hint = String; }
+#[cfg(feature = "repl")]
+impl Highlighter for Words { }
+#[cfg(feature = "repl")]
+impl Validator for Words { }
+#[cfg(feature = "repl")]
+impl Helper for Words { }
+
+/// `Lines` holds the lines entered so far that the virtual machine has not
+/// yet read.
+#[cfg(feature = "repl")]
+struct Lines {
+	buffer: VecDeque<u8>,
+	eof: bool,
+}
+
+#[cfg(feature = "repl")]
+impl Input for Lines {
+	fn getc(&mut self) -> Option<u8> { self.buffer.pop_front() }
+	fn available(&mut self) -> bool { self.eof || !self.buffer.is_empty() }
+}
+
+/// `Stream` adapts standard input or output for the virtual machine.
+struct Stream<T>(T);
+
+impl<R: Read> Input for Stream<R> {
+	fn getc(&mut self) -> Option<u8> {
+		let mut u: [u8; 1] = [0];
+		if 1 == self.0.read(&mut u).unwrap_or(0) { Some(u[0]) } else { None }
+	}
+}
+
+impl<W: Write> Output for Stream<W> {
+	fn putc(&mut self, c: u8) -> bool { self.0.write_all(&[c]).is_ok() }
+}
+
+/// `exit_code` turns the outcome of running the virtual machine into the
+/// exit code of the process.
+fn exit_code(outcome: Outcome) -> i32 {
+	match outcome {
+		Outcome::Halted(code) => code,
+		Outcome::Timeout => { eprintln!("eforth: limit reached"); TIMEOUT }
+		Outcome::Running | Outcome::NeedInput => 0,
+	}
+}
+
+/// `interactive` runs the virtual machine with a line editor, with history
+/// and, if `complete` is set, tab completion, feeding it a line of input at
+/// a time.
+#[cfg(feature = "repl")]
+fn interactive(vm: &mut VM, limits: &Limits, store: &mut dyn Store, complete: bool) -> i32 {
+	let mut editor: Editor<Words, DefaultHistory> = match Editor::new() {
+		Ok(editor) => editor,
+		Err(_) => return exit_code(vm.run_limited(limits, store, &mut Stream(io::stdin()), &mut Stream(io::stdout()))),
+	};
+	editor.set_helper(Some(Words(Vec::new())));
+	let history = std::env::home_dir().map(|home| home.join(HISTORY));
+	if let Some(ref history) = history { let _ignore = editor.load_history(history); }
+
+	let mut input = Lines { buffer: VecDeque::new(), eof: false };
+	let mut output = Stream(io::stdout());
+	let outcome = loop {
+		match vm.run_limited(limits, store, &mut input, &mut output) {
+			Outcome::NeedInput => {
+				let _ignore = output.0.flush();
+				if let Some(helper) = editor.helper_mut().filter(|_| complete) { helper.0 = words(vm) }
+				match editor.readline("") {
+					Ok(line) => {
+						let _ignore = editor.add_history_entry(line.as_str());
+						input.buffer.extend(line.bytes());
+						input.buffer.push_back(b'\n');
+					}
+					Err(ReadlineError::Interrupted) => { }
+					Err(_) => input.eof = true,
+				}
+			}
+			outcome => break outcome,
+		}
+	};
+	if let Some(ref history) = history { let _ignore = editor.save_history(history); }
+	exit_code(outcome)
+}
+
+/// `console` runs the virtual machine on the standard streams, through the
+/// line editor if standard input is a terminal, completing words if
+/// `complete` is set.
+#[cfg_attr(not(feature = "repl"), allow(unused_variables))]
+fn console(vm: &mut VM, limits: &Limits, store: &mut dyn Store, complete: bool) -> i32 {
+	#[cfg(feature = "repl")]
+	{
+		if io::stdin().is_terminal() { return interactive(vm, limits, store, complete) }
+	}
+	exit_code(vm.run_limited(limits, store, &mut Stream(io::stdin()), &mut Stream(io::stdout())))
+}
+
+/// `Waiting` is input that is never available.
+struct Waiting;
+
+impl Input for Waiting {
+	fn getc(&mut self) -> Option<u8> { None }
+	fn available(&mut self) -> bool { false }
+}
+
+/// `Pending` is input that is available until it runs out.
+struct Pending<'a>(&'a [u8]);
+
+impl<'a> Input for Pending<'a> {
+	fn getc(&mut self) -> Option<u8> { self.0.getc() }
+	fn available(&mut self) -> bool { !self.0.is_empty() }
+}
+
+/// `boot` runs the image until it first waits for input, printing its
+/// banner to `output`.
+///
+/// # Returns
+///
+/// The exit code, if the image halted or a limit was reached instead.
+fn boot(vm: &mut VM, limits: &Limits, store: &mut dyn Store, output: &mut dyn Output) -> Option<i32> {
+	match vm.run_limited(limits, store, &mut Waiting, output) {
+		Outcome::NeedInput => None,
+		outcome => Some(exit_code(outcome)),
+	}
+}
+
+/// `block_words` boots the image, printing its banner to `output`, and then
+/// defines the block words in it, discarding what they print.
+///
+/// # Returns
+///
+/// The exit code, if the image halted or a limit was reached.
+fn block_words(vm: &mut VM, limits: &Limits, store: &mut dyn Store, output: &mut dyn Output) -> Option<i32> {
+	if let Some(code) = boot(vm, limits, store, output) { return Some(code) }
+	match vm.run_limited(limits, store, &mut Pending(WORDS.as_bytes()), &mut ()) {
+		Outcome::NeedInput => None,
+		outcome => Some(exit_code(outcome)),
+	}
+}
+
+/// `prelude` returns Forth source that turns off the " ok" prompt and
+/// defines `argc ( -- n )` and `argv ( n -- c-addr u )`, which give access
+/// to the command line arguments, the first being the name of the script.
+/// The arguments are stored as counted strings built a byte at a time, so
+/// they need no quoting, but are limited to 255 bytes each.
+fn prelude(args: &[String]) -> String {
+	let mut source = String::from(": (quiet) ; ' (quiet) <ok> !\n");
+	source.push_str("variable (base) base @ (base) ! decimal\n");
+	for (i, arg) in args.iter().enumerate() {
+		let bytes = &arg.as_bytes()[..arg.len().min(255)];
+		source.push_str(&format!("create (arg{}) {} c,\n", i, bytes.len()));
+		for chunk in bytes.chunks(8) {
+			let line: Vec<String> = chunk.iter().map(|b| format!("{} c,", b)).collect();
+			source.push_str(&line.join(" "));
+			source.push('\n');
+		}
+		source.push_str("align\n");
+	}
+	source.push_str("create (argv)\n");
+	for i in 0..args.len() {
+		source.push_str(&format!("(arg{}) ,\n", i));
+	}
+	source.push_str(&format!(": argc {} ;\n", args.len()));
+	source.push_str(": argv cells (argv) + @ count ;\n");
+	source.push_str("(base) @ base !\n");
+	source
+}
+
+/// `script` runs a Forth script with the given command line arguments,
+/// without the start up banner or prompts. A first line starting with "#!"
+/// is skipped, so scripts can be made executable.
+///
+/// # Returns
+///
+/// The value passed to `bye`, or zero if the end of the script is reached.
+fn script(vm: &mut VM, source: &[u8], args: &[String], limits: &Limits, store: &mut dyn Store, output: &mut dyn Write) -> i32 {
+	let mut source = source;
+	if source.starts_with(b"#!") {
+		source = source.iter().position(|&c| c == b'\n').map_or(&[], |i| &source[i + 1..]);
+	}
+	let mut input = prelude(args).into_bytes();
+	input.extend_from_slice(source);
+	if input.last() != Some(&b'\n') { input.push(b'\n') }
+
+	if let Some(code) = boot(vm, limits, store, &mut ()) { return code }
+	exit_code(vm.run_limited(limits, store, &mut &input[..], &mut Stream(output)))
+}
+
+fn fail(message: &str) -> ! {
+	eprintln!("{}\n{}", message, USAGE);
+	process::exit(1);
+}
+
+/// `image` loads the image in the file `name`, in the format given by its
+/// extension.
+fn image(name: &str) -> VM {
+	let format = Path::new(name).extension().and_then(|e| e.to_str()).and_then(Format::from_extension).unwrap_or(Format::Raw);
+	let mut file = fs::File::open(name).unwrap_or_else(|e| fail(&format!("failed to open \"{}\": {}", name, e)));
+	let mut vm = VM::new();
+	if let Err(e) = vm.load_from(&mut file, format) { fail(&format!("failed to load \"{}\": {}", name, e)) }
+	vm
+}
 
 fn main()
 {
-	/*
-	let args: Vec<_> = env::args().collect();
-	if args.len() != 2 && args.len() != 3 {
-		panic!("Usage: {} vm.blk new.blk?");
+	let mut vm = VM::new();
+	let mut limits = Limits::default();
+	let mut blocks = None;
+	let mut complete = true;
+	let mut args = env::args().skip(1).peekable();
+	while let Some(option) = args.next_if(|arg| arg.starts_with('-')) {
+		let mut value = || args.next().unwrap_or_else(|| fail(&format!("expected a value after \"{}\"", option)));
+		match option.as_str() {
+			"-i" => { vm = image(&value()); complete = false }
+			"-c" => limits.cycles = Some(value().parse().unwrap_or_else(|_| fail("invalid cycle count"))),
+			"-t" => limits.time = Some(value().parse().ok().and_then(|t| Duration::try_from_secs_f64(t).ok()).unwrap_or_else(|| fail("invalid time"))),
+			"-b" => blocks = Some(value()),
+			"-h" => { println!("{}", USAGE); return }
+			_ => fail(&format!("unknown option \"{}\"", option)),
+		}
 	}
+	let args: Vec<String> = args.collect();
 
-	let new = if args.len() == 3 { &args[2] } else { &args[1] };
+	let mut store: Box<dyn Store> = match blocks {
+		Some(name) => {
+			let file = BlockFile::open(&name).unwrap_or_else(|e| fail(&format!("failed to open \"{}\": {}", name, e)));
+			let mut file = Box::new(file);
+			let banner: &mut dyn Output = if args.is_empty() { &mut Stream(io::stdout()) } else { &mut () };
+			if let Some(code) = block_words(&mut vm, &limits, &mut *file, banner) { process::exit(code) }
+			file
+		}
+		None => Box::new(()),
+	};
 
-	let mut vm = embed::VM::new();
-	let mut file = File::open(&Path::new(&args[1])).unwrap();
-	vm.load(&mut file); */
-
-	let mut vm = embed::VM::new();
-	std::process::exit(vm.run(None, &mut std::io::stdin(), &mut std::io::stdout()));
+	let code = if let Some(name) = args.first() {
+		let source = fs::read(name).unwrap_or_else(|e| fail(&format!("failed to read \"{}\": {}", name, e)));
+		if VM::from_bytes(&source).is_ok() { fail(&format!("\"{}\" is an image, not a script, run it with -i", name)) }
+		script(&mut vm, &source, &args, &limits, &mut *store, &mut io::stdout())
+	} else {
+		console(&mut vm, &limits, &mut *store, complete)
+	};
+	let _ignore = io::stdout().flush();
+	process::exit(code);
 }
 
+#[cfg(all(test, feature = "eforth"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dictionary() {
+		let mut vm = VM::new();
+		let names = words(&vm);
+		for name in &["dup", "words", "forth-wordlist", "(bye)"] {
+			assert!(names.iter().any(|n| n == name), "missing {}", name);
+		}
+		assert!(!names.iter().any(|n| n == "frobnicate"));
+		vm.execute(&mut (), &mut &b": frobnicate ; bye\n"[..], &mut ());
+		assert!(words(&vm).iter().any(|n| n == "frobnicate"));
+	}
+
+	#[test]
+	fn scripts() {
+		let args = vec!["test.fth".to_string(), "hello".to_string(), "\" \\".to_string()];
+		let source = b"#! /usr/bin/env eforth\nargc . 1 argv type 2 argv type cr\n1 2 + . cr\n3 (bye)\n4 (bye)\n";
+		let mut output = Vec::new();
+		assert_eq!(script(&mut VM::new(), source, &args, &Limits::default(), &mut (), &mut output), 3);
+		assert_eq!(String::from_utf8_lossy(&output), " 3hello\" \\\r\n 3\r\n");
+
+		let mut output = Vec::new();
+		assert_eq!(script(&mut VM::new(), b"base @ .", &args, &Limits::default(), &mut (), &mut output), 0);
+		assert_eq!(output, b" 10");
+
+		let limits = Limits { cycles: Some(10_000_000), ..Default::default() };
+		assert_eq!(script(&mut VM::new(), b": x begin again ; x", &args, &limits, &mut (), &mut Vec::new()), TIMEOUT);
+	}
+
+	#[test]
+	fn blocks() {
+		let mut vm = VM::new();
+		let mut blocks = embed::block::Blocks::new(2);
+		blocks.blocks()[1][..10].copy_from_slice(b"6 7 * . cr");
+		assert_eq!(block_words(&mut vm, &Limits::default(), &mut blocks, &mut ()), None);
+		let limits = Limits { cycles: Some(1000), ..Default::default() };
+		assert_eq!(block_words(&mut VM::new(), &limits, &mut blocks, &mut ()), Some(TIMEOUT));
+		let mut output = Vec::new();
+		assert_eq!(script(&mut vm, b"1 load", &["test.fth".to_string()], &Limits::default(), &mut blocks, &mut output), 0);
+		assert_eq!(String::from_utf8_lossy(&output), " 2A\r\n");
+	}
+}
@@ -0,0 +1,198 @@
+//! # Python Bindings for the Embed Virtual Machine
+//!
+//! With the `python` feature enabled this crate builds the Python module
+//! `embedvm`, which has a single class `VM` for scripting the virtual
+//! machine, for example from hardware test benches. Build the module with
+//! the `extension-module` feature, or with [maturin](https://www.maturin.rs/),
+//! to get a library Python can import:
+//!
+//! ```python
+//! import embedvm
+//! vm = embedvm.VM()
+//! print(vm.eval("2 3 + ."))
+//! ```
+//!
+//! Input and output are buffered, as they are for the WebAssembly bindings,
+//! so the virtual machine never blocks waiting for input. `step` and `run`
+//! say why they stopped with one of the module constants `RUNNING`,
+//! `NEED_INPUT`, `TIMEOUT` and `HALTED`, the value the virtual machine
+//! halted with is then `exit_code`.
+
+extern crate embed;
+#[cfg(feature = "python")]
+extern crate core;
+#[cfg(feature = "python")]
+extern crate pyo3;
+
+#[cfg(feature = "python")]
+use std::collections::VecDeque;
+
+#[cfg(feature = "python")]
+use embed::{Limits, Outcome, VM};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIOError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+
+/// `CYCLES` is the default limit on the number of instructions `run` and
+/// `eval` execute, enough for eForth to boot and interpret a few lines.
+#[cfg(feature = "python")]
+const CYCLES: u64 = 100_000_000;
+
+/// `RUNNING`, `NEED_INPUT`, `TIMEOUT` and `HALTED` are what `step` and `run`
+/// return when they stop, after executing as many instructions as `step` was
+/// allowed to, before reading input that has not been fed yet, on reaching
+/// the limit given to `run`, or when the virtual machine halts.
+#[cfg(feature = "python")]
+const RUNNING: &str = "running";
+#[cfg(feature = "python")]
+const NEED_INPUT: &str = "need_input";
+#[cfg(feature = "python")]
+const TIMEOUT: &str = "timeout";
+#[cfg(feature = "python")]
+const HALTED: &str = "halted";
+
+/// `Vm` is the Python class `VM`, a virtual machine along with its input and
+/// output buffers.
+#[cfg(feature = "python")]
+#[pyclass(name = "VM")]
+struct Vm {
+	vm: VM,
+	input: VecDeque<u8>,
+	output: Vec<u8>,
+	code: Option<i32>,
+}
+
+#[cfg(feature = "python")]
+impl Vm {
+	/// `status` records the exit code if the virtual machine halted and
+	/// turns `outcome` into the status returned to Python.
+	fn status(&mut self, outcome: Outcome) -> &'static str {
+		match outcome {
+			Outcome::Halted(r) => { self.code = Some(r); HALTED }
+			Outcome::Running => RUNNING,
+			Outcome::NeedInput => NEED_INPUT,
+			Outcome::Timeout => TIMEOUT,
+		}
+	}
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Vm {
+	/// `VM(image=None)` creates a virtual machine from an image in a bytes
+	/// object, or with the built in eForth image if none is given.
+	#[new]
+	#[pyo3(signature = (image=None))]
+	fn new(image: Option<&[u8]>) -> PyResult<Self> {
+		let vm = match image {
+			Some(image) => VM::from_bytes(image).map_err(|e| PyValueError::new_err(e.to_string()))?,
+			None => VM::new(),
+		};
+		Ok(Vm { vm, input: VecDeque::new(), output: Vec::new(), code: None })
+	}
+
+	/// `load(image)` replaces the image being run and resets the registers.
+	fn load(&mut self, image: &[u8]) -> PyResult<()> {
+		self.vm = VM::from_bytes(image).map_err(|e| PyValueError::new_err(e.to_string()))?;
+		self.code = None;
+		Ok(())
+	}
+
+	/// `save()` returns the memory of the virtual machine as an image, it
+	/// raises `IOError` if the image could not be written.
+	fn save<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+		let mut image = Vec::new();
+		self.vm.save(&mut image).ok_or_else(|| PyIOError::new_err("failed to save image"))?;
+		Ok(PyBytes::new(py, &image))
+	}
+
+	/// `step(cycles)` executes at most `cycles` instructions, returning
+	/// `RUNNING` if it executed them all, or `NEED_INPUT` or `HALTED` if it
+	/// stopped early.
+	fn step(&mut self, cycles: u64) -> &'static str {
+		let outcome = self.vm.step(cycles, &mut (), &mut self.input, &mut self.output);
+		self.status(outcome)
+	}
+
+	/// `run(cycles=CYCLES)` runs until the virtual machine needs more input
+	/// or halts, returning `NEED_INPUT` or `HALTED`, or `TIMEOUT` if it
+	/// executed `cycles` instructions without doing either.
+	#[pyo3(signature = (cycles=CYCLES))]
+	fn run(&mut self, cycles: u64) -> &'static str {
+		let limits = Limits { cycles: Some(cycles), ..Default::default() };
+		let outcome = self.vm.run_limited(&limits, &mut (), &mut self.input, &mut self.output);
+		self.status(outcome)
+	}
+
+	/// `exit_code` is the value the virtual machine halted with, or None if
+	/// it has not halted.
+	#[getter]
+	fn exit_code(&self) -> Option<i32> {
+		self.code
+	}
+
+	/// `feed(data)` appends bytes to the input buffer.
+	fn feed(&mut self, data: &[u8]) {
+		self.input.extend(data);
+	}
+
+	/// `drain()` removes and returns everything in the output buffer.
+	fn drain<'py>(&mut self, py: Python<'py>) -> Bound<'py, PyBytes> {
+		let output = PyBytes::new(py, &self.output);
+		self.output.clear();
+		output
+	}
+
+	/// `eval(source, cycles=CYCLES)` feeds a line of Forth to the virtual
+	/// machine, runs it until it is waiting for more input, halts or
+	/// reaches the cycle limit, and returns any output as a string.
+	#[pyo3(signature = (source, cycles=CYCLES))]
+	fn eval(&mut self, source: &str, cycles: u64) -> String {
+		self.input.extend(source.as_bytes());
+		self.input.push_back(b'\n');
+		self.run(cycles);
+		let output = String::from_utf8_lossy(&self.output).into_owned();
+		self.output.clear();
+		output
+	}
+
+	/// `push(value)` pushes a cell onto the variable stack.
+	fn push(&mut self, value: u16) {
+		self.vm.push(value);
+	}
+
+	/// `pop()` pops a cell from the variable stack, or returns None if it is
+	/// empty.
+	fn pop(&mut self) -> Option<u16> {
+		self.vm.pop()
+	}
+
+	/// `depth()` is the number of cells on the variable stack.
+	fn depth(&self) -> u16 {
+		self.vm.depth()
+	}
+
+	/// `read(address)` returns the cell at a cell address.
+	fn read(&self, address: u16) -> u16 {
+		self.vm.read(address)
+	}
+
+	/// `write(address, value)` sets the cell at a cell address.
+	fn write(&mut self, address: u16, value: u16) {
+		self.vm.write(address, value);
+	}
+}
+
+/// `embedvm` is the Python module.
+#[cfg(feature = "python")]
+#[pymodule]
+fn embedvm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+	m.add_class::<Vm>()?;
+	m.add("RUNNING", RUNNING)?;
+	m.add("NEED_INPUT", NEED_INPUT)?;
+	m.add("TIMEOUT", TIMEOUT)?;
+	m.add("HALTED", HALTED)
+}
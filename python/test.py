@@ -0,0 +1,55 @@
+"""Tests for the Python bindings, run by "tests/python.rs" with the built
+module on PYTHONPATH, or by hand with "python3 test.py"."""
+import unittest
+
+import embedvm
+
+
+class TestVM(unittest.TestCase):
+    def test_eval(self):
+        vm = embedvm.VM()
+        self.assertEqual(vm.run(), embedvm.NEED_INPUT)
+        self.assertIsNone(vm.exit_code)
+        self.assertIn(b"eFORTH", vm.drain())
+        self.assertIn(" 5", vm.eval("decimal 2 3 + ."))
+        self.assertIn("dup", vm.eval("words"))
+        vm.feed(b"7 (bye)\n")
+        self.assertEqual(vm.run(), embedvm.HALTED)
+        self.assertEqual(vm.exit_code, 7)
+
+    def test_outcomes(self):
+        vm = embedvm.VM()
+        self.assertEqual(vm.step(10), embedvm.RUNNING)
+        self.assertEqual(vm.step(100000000), embedvm.NEED_INPUT)
+        vm.feed(b": x begin again ; x\n")
+        self.assertEqual(vm.run(100000), embedvm.TIMEOUT)
+        self.assertEqual(vm.step(10), embedvm.RUNNING)
+        self.assertIsNone(vm.exit_code)
+
+    def test_stack_and_memory(self):
+        vm = embedvm.VM()
+        self.assertEqual(vm.depth(), 0)
+        vm.push(3)
+        vm.push(4)
+        self.assertEqual(vm.depth(), 2)
+        self.assertEqual(vm.pop(), 4)
+        self.assertEqual(vm.pop(), 3)
+        self.assertIsNone(vm.pop())
+        vm.write(0x4000, 0x1234)
+        self.assertEqual(vm.read(0x4000), 0x1234)
+
+    def test_images(self):
+        vm = embedvm.VM()
+        image = vm.save()
+        self.assertEqual(image[4:8], b"\x89\x46\x54\x48")
+        copy = embedvm.VM(image)
+        self.assertEqual(copy.read(0), vm.read(0))
+        copy.load(image)
+        with self.assertRaises(ValueError):
+            embedvm.VM(b"not an image")
+        with self.assertRaises(ValueError):
+            copy.load(b"")
+
+
+if __name__ == "__main__":
+    unittest.main()
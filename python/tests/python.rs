@@ -0,0 +1,27 @@
+//! Copies the built extension module to where Python can import it and runs
+//! `test.py`. The test is skipped if there is no Python interpreter, which can
+//! be set with the `PYTHON` environment variable.
+#![cfg(feature = "python")]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn python() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+	let exe = env::current_exe().unwrap();
+	let lib = exe.parent().unwrap().join("libembedvm.so");
+	let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("python");
+	let python = env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+
+	fs::create_dir_all(&out).unwrap();
+	fs::copy(&lib, out.join("embedvm.so")).unwrap();
+	let status = Command::new(&python).arg(dir.join("test.py")).env("PYTHONPATH", &out).status();
+	let status = match status {
+		Err(e) => { eprintln!("skipping Python test, could not run \"{}\": {}", python, e); return }
+		Ok(status) => status,
+	};
+	assert!(status.success(), "test.py failed");
+}
@@ -0,0 +1,380 @@
+//! # Reference Model
+//!
+//! `Reference` is a second, independent, implementation of the virtual
+//! machine, written from the description of the instruction set for clarity
+//! and not for speed, so that the interpreter in `VM::step` can be checked
+//! against it. `compare` runs an image on both one instruction at a time and
+//! reports the first difference in the registers, memory, output or halting,
+//! it is used by the differential fuzzing target in **fuzz/** and the tests.
+//!
+//! Every instruction is a single cell, decoded by its top bits:
+//!
+//! | Bits    | Instruction | Effect                                       |
+//! | ------- | ----------- | -------------------------------------------- |
+//! | `1vvv`  | literal     | push the 15-bit value `v`                    |
+//! | `011`   | ALU         | an operation and its flags, described below  |
+//! | `010`   | call        | push the return address, jump to the target  |
+//! | `001`   | 0branch     | jump to the target if T is zero, drop T      |
+//! | `000`   | branch      | jump to the 13-bit target                    |
+//!
+//! An ALU instruction has its operation in bits 8 to 12, the flags R->PC,
+//! N->T, T->R and T->N in bits 4 to 7, the return stack delta in bits 2 and 3
+//! and the variable stack delta in bits 0 and 1. The operation gives a new
+//! top of stack, then the deltas are applied and the flags copy values
+//! between the stacks, with T and N as left by the operation, the division
+//! operations replace both with the remainder. Return addresses are stored
+//! as byte addresses, as are the addresses used by the memory operations.
+//! Memory is 32768 cells, addresses wrap around at its end and arithmetic
+//! wraps, shifts by 16 or more give zero. The save operation is given the
+//! byte address of the first cell and a length in bytes, rounded up to whole
+//! cells, and fails if they run past the end of memory. The block operations
+//! move the 512 cells of a buffer to or from a block of 1024 characters, low
+//! byte first.
+
+use decode::Op;
+use host::{Input, Output, Store, BLOCK_SIZE};
+use image::ByteOrder;
+use {VM, CORE_SIZE, RP0, SP0};
+
+/// `Reference` holds the state of the reference model, the registers and
+/// the memory.
+#[derive(Clone)]
+pub struct Reference {
+	/// `pc` is the program counter, a cell address
+	pub pc: u16,
+	/// `t` is the top of the variable stack
+	pub t: u16,
+	/// `sp` points to the next on the variable stack
+	pub sp: u16,
+	/// `rp` points to the top of the return stack
+	pub rp: u16,
+	/// `core` is all of memory
+	pub core: [u16; CORE_SIZE],
+	/// `written` holds the cells written to by the last instruction
+	written: Written,
+}
+
+/// `WRITTEN` is the most cells an instruction can write to, the block read
+/// operation writes a whole buffer and its flags two more, every other
+/// instruction writes at most three.
+const WRITTEN: usize = BLOCK_SIZE / 2 + 2;
+
+/// `Written` is a list of the cells written to by an instruction.
+#[derive(Clone, Copy)]
+struct Written {
+	cells: [u16; WRITTEN],
+	count: usize,
+}
+
+impl Default for Written {
+	fn default() -> Self { Written { cells: [0; WRITTEN], count: 0 } }
+}
+
+impl Written {
+	fn push(&mut self, address: u16) {
+		self.cells[self.count] = address;
+		self.count += 1;
+	}
+
+	fn iter(&self) -> impl Iterator<Item = &u16> { self.cells[..self.count].iter() }
+}
+
+impl Reference {
+	/// `new` starts a model with `image` at the start of memory, and the
+	/// registers as `VM::reset` leaves them.
+	pub fn new(image: &[u16]) -> Self {
+		let mut r = Reference { pc: 0, t: 0, sp: SP0, rp: RP0, core: [0; CORE_SIZE], written: Written::default() };
+		for (c, &i) in r.core.iter_mut().zip(image) { *c = i }
+		r
+	}
+
+	fn load(&self, address: u16) -> u16 { self.core[address as usize % CORE_SIZE] }
+
+	fn store(&mut self, address: u16, value: u16) {
+		self.core[address as usize % CORE_SIZE] = value;
+		self.written.push((address as usize % CORE_SIZE) as u16);
+	}
+
+	/// `step` executes one instruction.
+	///
+	/// # Returns
+	///
+	/// True if the instruction halted the model, which leaves it unchanged.
+	pub fn step(&mut self, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> bool {
+		self.written = Written::default();
+		let instruction = self.load(self.pc);
+		let next = (self.pc + 1) % CORE_SIZE as u16;
+		let target = instruction & 0x1fff;
+
+		if instruction & 0x8000 != 0 {
+			self.sp = self.sp.wrapping_add(1);
+			self.store(self.sp, self.t);
+			self.t = instruction & 0x7fff;
+			self.pc = next;
+			return false;
+		}
+
+		match instruction >> 13 {
+			0 => self.pc = target,
+			1 => {
+				self.pc = if self.t == 0 { target } else { next };
+				self.t = self.load(self.sp);
+				self.sp = self.sp.wrapping_sub(1);
+			}
+			2 => {
+				self.rp = self.rp.wrapping_sub(1);
+				self.store(self.rp, next << 1);
+				self.pc = target;
+			}
+			_ => return self.alu(instruction, next, store, input, output),
+		}
+		false
+	}
+
+	fn alu(&mut self, instruction: u16, next: u16, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> bool {
+		let (t, n, r) = (self.t, self.load(self.sp), self.load(self.rp));
+		let mut pc = if instruction & 0x10 != 0 { r >> 1 } else { next };
+		let flag = |b: bool| if b { 0xffff } else { 0 };
+
+		/* the new top of stack, and what T and N are for the flags */
+		let (top, t, n) = match (instruction >> 8) & 0x1f {
+			0 => (t, t, n),
+			1 => (n, t, n),
+			2 => (r, t, n),
+			3 => (self.load(t >> 1), t, n),
+			4 => {
+				self.store(t >> 1, n);
+				self.sp = self.sp.wrapping_sub(1);
+				(self.load(self.sp), t, n)
+			}
+			5 => {
+				let sum = t as u32 + n as u32;
+				self.store(self.sp, sum as u16);
+				((sum >> 16) as u16, t, sum as u16)
+			}
+			6 => {
+				let product = t as u32 * n as u32;
+				self.store(self.sp, product as u16);
+				((product >> 16) as u16, t, product as u16)
+			}
+			7 => (t & n, t, n),
+			8 => (t | n, t, n),
+			9 => (t ^ n, t, n),
+			10 => (!t, t, n),
+			11 => (t.wrapping_sub(1), t, n),
+			12 => (flag(t == 0), t, n),
+			13 => (flag(t == n), t, n),
+			14 => (flag(n < t), t, n),
+			15 => (flag((n as i16) < (t as i16)), t, n),
+			16 => (if t < 16 { n >> t } else { 0 }, t, n),
+			17 => (if t < 16 { n << t } else { 0 }, t, n),
+			18 => (self.sp << 1, t, n),
+			19 => (self.rp << 1, t, n),
+			20 => { self.sp = t >> 1; (t, t, n) }
+			21 => { self.rp = t >> 1; (n, t, n) }
+			22 => {
+				let (start, length) = ((n >> 1) as usize, ((t as usize) + 1) >> 1);
+				let saved = start + length <= CORE_SIZE && store.store(&self.core[start..start + length], ByteOrder::Little);
+				(flag(!saved), t, n)
+			}
+			23 => (if output.putc(t as u8) { t & 0xff } else { 0xffff }, t, n),
+			24 => (input.getc().map_or(0xffff, u16::from), t, n),
+			25 | 26 if t == 0 => { pc = 1; (10, t, n) }
+			25 => (n / t, n % t, n % t),
+			26 => {
+				let (n, t) = (n as i16, t as i16);
+				(n.wrapping_div(t) as u16, n.wrapping_rem(t) as u16, n.wrapping_rem(t) as u16)
+			}
+			27 => return true,
+			28 => {
+				let start = (n >> 1) as usize;
+				let mut block = [0; BLOCK_SIZE];
+				let read = start + BLOCK_SIZE / 2 <= CORE_SIZE && store.read_block(t, &mut block);
+				if read {
+					for i in 0..BLOCK_SIZE / 2 {
+						self.store((start + i) as u16, block[2 * i] as u16 | (block[2 * i + 1] as u16) << 8);
+					}
+				}
+				(flag(!read), t, n)
+			}
+			29 => {
+				let start = (n >> 1) as usize;
+				let mut block = [0; BLOCK_SIZE];
+				let fits = start + BLOCK_SIZE / 2 <= CORE_SIZE;
+				if fits {
+					for i in 0..BLOCK_SIZE / 2 {
+						let c = self.core[start + i];
+						block[2 * i] = c as u8;
+						block[2 * i + 1] = (c >> 8) as u8;
+					}
+				}
+				(flag(!(fits && store.write_block(t, &block))), t, n)
+			}
+			_ => (t, t, n),
+		};
+
+		let delta = |bits: u16| [0, 1, 0xfffe, 0xffff][bits as usize & 3];
+		self.sp = self.sp.wrapping_add(delta(instruction));
+		self.rp = self.rp.wrapping_sub(delta(instruction >> 2));
+		self.t = if instruction & 0x20 != 0 { n } else { top };
+		if instruction & 0x40 != 0 { self.store(self.rp, t) }
+		if instruction & 0x80 != 0 { self.store(self.sp, t) }
+		self.pc = pc;
+		false
+	}
+}
+
+/// `Divergence` is the first difference `compare` found between the virtual
+/// machine and the reference model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+	/// `step` is the number of instructions executed before the one that
+	/// caused it
+	pub step: usize,
+	/// `pc` is the address of that instruction
+	pub pc: u16,
+	/// `instruction` is the instruction
+	pub instruction: u16,
+	/// `what` differs, "pc", "t", "sp", "rp", "halted", "output" or "core"
+	pub what: &'static str,
+	/// `address` is the first cell that differs, if `what` is "core"
+	pub address: u16,
+	/// `vm` is the value the virtual machine has
+	pub vm: u16,
+	/// `reference` is the value the reference model has
+	pub reference: u16,
+}
+
+/// `Last` keeps the last character written in a step.
+struct Last(Option<u8>);
+
+impl Output for Last {
+	fn putc(&mut self, c: u8) -> bool { self.0 = Some(c); true }
+}
+
+/// `compare` runs `image`, with `input`, on a `VM` and a `Reference` side by
+/// side for at most `steps` instructions, comparing them after each one, by
+/// their registers, output and the cells either wrote to, and all of memory
+/// at the end. The save instruction is refused by both.
+///
+/// # Returns
+///
+/// The number of instructions executed, or the first `Divergence`.
+///
+/// # Example
+///
+/// ```
+/// use embed::reference::compare;
+/// assert_eq!(compare(&[0x8002, 0x8003, 0x6523, 0x7b00], b"", 100), Ok(4));
+/// ```
+pub fn compare(image: &[u16], input: &[u8], steps: usize) -> Result<usize, Divergence> {
+	let mut vm = VM::blank();
+	for (c, &i) in vm.core.iter_mut().zip(image) { *c = i }
+	let mut reference = Reference::new(image);
+	let (mut vm_input, mut reference_input) = (input, input);
+
+	let mut executed = steps;
+	for step in 0..steps {
+		let (pc, instruction) = (reference.pc, reference.load(reference.pc));
+		let (mut vm_output, mut reference_output) = (Last(None), Last(None));
+		let mut r = vm.registers();
+		let mut written = Written::default();
+		let halted = VM::exec(Op::decode(vm.core[r.pc as usize]), &mut r, &mut vm.core, vm.order, &mut (), &mut vm_input, &mut vm_output, &mut |a| written.push(a));
+		vm.set_registers(r);
+		let reference_halted = reference.step(&mut (), &mut reference_input, &mut reference_output);
+
+		let divergence = |what, address, vm, reference| Divergence { step, pc, instruction, what, address, vm, reference };
+		let registers = [
+			("halted", halted as u16, reference_halted as u16),
+			("output", vm_output.0.map_or(0xffff, u16::from), reference_output.0.map_or(0xffff, u16::from)),
+			("pc", vm.pc, reference.pc),
+			("t", vm.t, reference.t),
+			("sp", vm.sp, reference.sp),
+			("rp", vm.rp, reference.rp),
+		];
+		for &(what, a, b) in registers.iter() {
+			if a != b { return Err(divergence(what, 0, a, b)) }
+		}
+		for &a in written.iter().chain(reference.written.iter()) {
+			let (v, w) = (vm.core[a as usize % CORE_SIZE], reference.load(a));
+			if v != w { return Err(divergence("core", a, v, w)) }
+		}
+		if halted { executed = step + 1; break }
+	}
+
+	if let Some(a) = (0..CORE_SIZE).find(|&a| vm.core[a] != reference.core[a]) {
+		let (pc, instruction) = (reference.pc, reference.load(reference.pc));
+		return Err(Divergence { step: executed, pc, instruction, what: "core", address: a as u16, vm: vm.core[a], reference: reference.core[a] });
+	}
+	Ok(executed)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// `Random` is a small xorshift generator, so the tests are repeatable.
+	struct Random(u32);
+
+	impl Random {
+		fn next(&mut self) -> u16 {
+			self.0 ^= self.0 << 13;
+			self.0 ^= self.0 >> 17;
+			self.0 ^= self.0 << 5;
+			(self.0 >> 8) as u16
+		}
+	}
+
+	#[test]
+	fn random() {
+		/* mostly ALU instructions, with jumps kept within the image */
+		let mut random = Random(0x2545_f491);
+		for _ in 0..300 {
+			let image: Vec<u16> = (0..256).map(|_| {
+				let r = random.next();
+				match r % 8 {
+					0 => r | 0x8000,
+					1 => 0x4000 | ((r >> 3) % 256),
+					2 => 0x2000 | ((r >> 3) % 256),
+					3 => (r >> 3) % 256,
+					_ => 0x6000 | (r & 0x1fff),
+				}
+			}).collect();
+			let input: Vec<u8> = (0..16).map(|_| random.next() as u8).collect();
+			if let Err(d) = compare(&image, &input, 2000) { panic!("{:x?}", d) }
+		}
+	}
+
+	/// `Filled` has every block filled with the same byte.
+	struct Filled(u8);
+
+	impl Store for Filled {
+		fn store(&mut self, _cells: &[u16], _order: ByteOrder) -> bool { false }
+		fn read_block(&mut self, _block: u16, buffer: &mut [u8; BLOCK_SIZE]) -> bool {
+			*buffer = [self.0; BLOCK_SIZE];
+			true
+		}
+	}
+
+	#[test]
+	fn block() {
+		/* a block read, with T->N and T->R, records every cell written */
+		let mut reference = Reference::new(&[0x8100, 0x8003, 0x7cc0]);
+		for _ in 0..3 {
+			assert!(!reference.step(&mut Filled(1), &mut (), &mut ()));
+		}
+		assert_eq!(reference.t, 0);
+		assert_eq!(reference.written.count, WRITTEN);
+		assert!(reference.written.iter().take(BLOCK_SIZE / 2).eq(&(0x80..0x280).collect::<Vec<u16>>()));
+		assert!((0x80..0x280).all(|a| reference.load(a) == 0x0101));
+	}
+
+	#[test]
+	#[cfg(feature = "eforth")]
+	fn eforth() {
+		let source = b"decimal 2 3 + . : x 1 2 + ; x . words -7 2 / . 7 0 /mod\n65535 8 rshift . 0 (bye)\n";
+		let steps = compare(::ImageId::Eforth.cells(), source, 1_000_000);
+		assert!(steps.is_ok(), "{:x?}", steps);
+		assert!(steps.unwrap() < 1_000_000);
+	}
+}
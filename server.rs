@@ -0,0 +1,315 @@
+//! # eForth Console Server
+//!
+//! Serves an eForth prompt over TCP, for remote maintenance with a telnet
+//! client. Every connection gets a fresh virtual machine, copied from the
+//! bundled eForth image or from an image file given on the command line,
+//! run on its own thread.
+//!
+//! Telnet option negotiation is handled by refusing every option, which
+//! leaves the client in its default line mode with local echo, and line
+//! endings are translated, "\r\n" or "\r\0" to "\n" on the way in and a lone
+//! "\n" to "\r\n" on the way out.
+//!
+//! Each session is limited in the number of instructions it may execute, in
+//! how long it may wait for input and by a save quota, the number of bytes
+//! it may save with the save instruction. Sessions cannot touch the disk,
+//! saved sections are only counted against the quota and then discarded,
+//! and the save instruction fails once the quota would be exceeded. For the
+//! same reason sessions are given no blocks, the block operations always
+//! fail, rather than sharing a block file between connections. Writes to
+//! memory of the session's own virtual machine are not limited.
+
+extern crate embed;
+
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use embed::{ByteOrder, Format, Input, Limits, Outcome, Output, Store, VM};
+
+const USAGE: &str = "usage: eforth-server [-i image] [-c cycles] [-s bytes] [-r seconds] [address]";
+
+/// `ADDRESS` is the default address to listen on, local connections only.
+const ADDRESS: &str = "127.0.0.1:2323";
+/// `CYCLES` is the default limit on instructions executed per session.
+const CYCLES: u64 = 1_000_000_000;
+/// `IDLE` is the default number of seconds a session may wait for input.
+const IDLE: u64 = 300;
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+/// `Config` holds the per session settings.
+struct Config {
+	/// `image` is copied for every new session
+	image: VM,
+	/// `cycles` is the maximum number of instructions a session may execute
+	cycles: u64,
+	/// `saves` is the save quota, the number of bytes a session may save
+	saves: usize,
+	/// `idle` is how long a session may wait for input before it is closed
+	idle: Duration,
+}
+
+/// `Telnet` reads characters from a telnet client, answering any option
+/// negotiation on `replies` and translating line endings.
+struct Telnet<R, W> {
+	reader: R,
+	replies: W,
+	cr: bool,
+}
+
+/// `Crlf` writes characters to a telnet client, translating line endings
+/// and escaping the byte used to start telnet commands.
+struct Crlf<W> {
+	writer: W,
+	cr: bool,
+}
+
+/// `Quota` counts the bytes a session saves, refusing any section that would
+/// take it over the limit. It has no blocks, so reading or writing one fails.
+struct Quota {
+	limit: usize,
+	saved: usize,
+}
+
+impl<R: Read, W: Write> Telnet<R, W> {
+	fn byte(&mut self) -> Option<u8> {
+		let mut u: [u8; 1] = [0];
+		if 1 == self.reader.read(&mut u).unwrap_or(0) { Some(u[0]) } else { None }
+	}
+
+	/// `command` handles a telnet command, the `IAC` has already been read.
+	fn command(&mut self) -> Option<Option<u8>> {
+		let command = self.byte()?;
+		let reply = match command {
+			IAC => return Some(Some(IAC)),
+			WILL => DONT,
+			DO => WONT,
+			WONT | DONT => { self.byte()?; return Some(None) }
+			SB => {
+				while self.byte()? != IAC || self.byte()? != SE { }
+				return Some(None);
+			}
+			_ => return Some(None),
+		};
+		let option = self.byte()?;
+		let _ignore = self.replies.write_all(&[IAC, reply, option]);
+		Some(None)
+	}
+}
+
+impl<R: Read, W: Write> Input for Telnet<R, W> {
+	fn getc(&mut self) -> Option<u8> {
+		loop {
+			let c = self.byte()?;
+			let cr = self.cr;
+			self.cr = c == b'\r';
+			match c {
+				IAC => if let Some(c) = self.command()? { return Some(c) },
+				b'\r' => return Some(b'\n'),
+				b'\n' | 0 if cr => { }
+				_ => return Some(c),
+			}
+		}
+	}
+}
+
+impl<W: Write> Output for Crlf<W> {
+	fn putc(&mut self, c: u8) -> bool {
+		let r = match c {
+			b'\n' if !self.cr => self.writer.write_all(b"\r\n"),
+			IAC => self.writer.write_all(&[IAC, IAC]),
+			_ => self.writer.write_all(&[c]),
+		};
+		self.cr = c == b'\r';
+		r.is_ok()
+	}
+}
+
+impl Store for Quota {
+	fn store(&mut self, cells: &[u16], _order: ByteOrder) -> bool {
+		if self.saved + cells.len() * 2 > self.limit { return false }
+		self.saved += cells.len() * 2;
+		true
+	}
+}
+
+/// `session` runs a virtual machine for one connection until it halts, the
+/// connection closes or is idle for too long, or the cycle limit is reached.
+///
+/// # Returns
+///
+/// The value the virtual machine halted with, or `None` if it did not.
+fn session(stream: &TcpStream, config: &Config) -> Option<i32> {
+	let mut vm = config.image.clone();
+	let _ignore = stream.set_read_timeout(Some(config.idle));
+	let mut input = Telnet { reader: stream, replies: stream, cr: false };
+	let mut output = Crlf { writer: stream, cr: false };
+	let mut quota = Quota { limit: config.saves, saved: 0 };
+	let limits = Limits { cycles: Some(config.cycles), ..Default::default() };
+	match vm.run_limited(&limits, &mut quota, &mut input, &mut output) {
+		Outcome::Halted(code) => Some(code),
+		_ => {
+			let _ignore = output.writer.write_all(b"\r\ncycle limit reached\r\n");
+			None
+		}
+	}
+}
+
+/// `close` shuts down the connection, reading any input left unread first
+/// so the client is not sent a reset, which could lose the last output.
+fn close(stream: &TcpStream) {
+	let _ignore = stream.shutdown(Shutdown::Write);
+	let _ignore = stream.set_read_timeout(Some(Duration::from_secs(1)));
+	let (mut reader, mut buffer) = (stream, [0u8; 256]);
+	while let Ok(n) = reader.read(&mut buffer) {
+		if n == 0 { break }
+	}
+}
+
+/// `serve` accepts connections forever, running each session on its own
+/// thread.
+fn serve(listener: TcpListener, config: Config) {
+	let config = Arc::new(config);
+	for stream in listener.incoming() {
+		let stream = match stream {
+			Ok(stream) => stream,
+			Err(e) => { eprintln!("accept failed: {}", e); continue }
+		};
+		let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+		let config = config.clone();
+		thread::spawn(move || {
+			eprintln!("{}: connected", peer);
+			match session(&stream, &config) {
+				Some(code) => eprintln!("{}: exited with {}", peer, code),
+				None => eprintln!("{}: closed", peer),
+			}
+			close(&stream);
+		});
+	}
+}
+
+fn fail(message: &str) -> ! {
+	eprintln!("{}\n{}", message, USAGE);
+	process::exit(1);
+}
+
+fn number<T: std::str::FromStr>(arg: Option<String>) -> T {
+	arg.and_then(|a| a.parse().ok()).unwrap_or_else(|| fail("expected a number"))
+}
+
+fn load(name: &str) -> VM {
+	let format = Path::new(name).extension().and_then(|e| e.to_str()).and_then(Format::from_extension).unwrap_or(Format::Raw);
+	let mut file = File::open(name).unwrap_or_else(|e| fail(&format!("failed to open \"{}\": {}", name, e)));
+	let mut vm = VM::new();
+	if let Err(e) = vm.load_from(&mut file, format) { fail(&format!("failed to load \"{}\": {}", name, e)) }
+	vm
+}
+
+fn main() {
+	let mut config = Config { image: VM::new(), cycles: CYCLES, saves: 0, idle: Duration::from_secs(IDLE) };
+	let mut address = ADDRESS.to_string();
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-i" => config.image = load(&args.next().unwrap_or_else(|| fail("expected an image"))),
+			"-c" => config.cycles = number(args.next()),
+			"-s" => config.saves = number(args.next()),
+			"-r" => config.idle = Duration::from_secs(number(args.next())),
+			"-h" => { println!("{}", USAGE); return }
+			_ if arg.starts_with('-') => fail(&format!("unknown option \"{}\"", arg)),
+			_ => address = arg,
+		}
+	}
+
+	let listener = TcpListener::bind(&address).unwrap_or_else(|e| fail(&format!("failed to listen on {}: {}", address, e)));
+	eprintln!("listening on {}", address);
+	serve(listener, config);
+}
+
+#[cfg(all(test, feature = "eforth"))]
+mod tests {
+	use super::*;
+	use embed::BLOCK_SIZE;
+
+	fn connect(config: Config) -> TcpStream {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || serve(listener, config));
+		TcpStream::connect(address).unwrap()
+	}
+
+	fn read_until(stream: &mut TcpStream, output: &mut Vec<u8>, expect: &[u8]) {
+		let mut buffer = [0u8; 256];
+		while !output.windows(expect.len()).any(|w| w == expect) {
+			let n = stream.read(&mut buffer).unwrap();
+			assert!(n > 0, "connection closed early");
+			output.extend_from_slice(&buffer[..n]);
+		}
+	}
+
+	#[test]
+	fn telnet() {
+		let mut replies = Vec::new();
+		let data = [b'a', b'\r', b'\n', IAC, DO, 1, b'b', b'\r', 0, IAC, IAC, IAC, SB, 24, 1, IAC, SE, b'c', b'\n'];
+		let mut input = Telnet { reader: &data[..], replies: &mut replies, cr: false };
+		let mut decoded = Vec::new();
+		while let Some(c) = input.getc() { decoded.push(c) }
+		assert_eq!(decoded, b"a\nb\n\xffc\n");
+		assert_eq!(replies, [IAC, WONT, 1]);
+
+		let mut output = Crlf { writer: Vec::new(), cr: false };
+		for &c in b"a\nb\r\n\xff" { output.putc(c); }
+		assert_eq!(output.writer, b"a\r\nb\r\n\xff\xff");
+	}
+
+	#[test]
+	fn sessions() {
+		let idle = Duration::from_secs(IDLE);
+		let mut stream = connect(Config { image: VM::new(), cycles: CYCLES, saves: 100, idle });
+		let mut output = Vec::new();
+		stream.write_all(b"decimal 2 3 + .\r\n").unwrap();
+		read_until(&mut stream, &mut output, b" 5");
+		stream.write_all(&[IAC, WILL, 31]).unwrap();
+		read_until(&mut stream, &mut output, &[IAC, DONT, 31]);
+		stream.write_all(b"0 100 (save) . 0 102 (save) .\r\nbye\r\n").unwrap();
+		stream.read_to_end(&mut output).unwrap();
+		assert!(String::from_utf8_lossy(&output).contains(" 0 -1 ok"));
+
+		let mut stream = connect(Config { image: VM::new(), cycles: 10_000_000, saves: 0, idle });
+		let mut output = Vec::new();
+		stream.write_all(b": x begin again ; x\r\n").unwrap();
+		stream.read_to_end(&mut output).unwrap();
+		assert!(String::from_utf8_lossy(&output).contains("cycle limit reached"));
+
+		/* a client that sends nothing is disconnected */
+		let mut stream = connect(Config { image: VM::new(), cycles: CYCLES, saves: 0, idle: Duration::from_millis(200) });
+		stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+		stream.read_to_end(&mut Vec::new()).unwrap();
+	}
+
+	#[test]
+	fn quota() {
+		let mut quota = Quota { limit: 6, saved: 0 };
+		assert!(quota.store(&[1, 2], ByteOrder::Little));
+		assert!(!quota.store(&[3, 4], ByteOrder::Big));
+		assert!(quota.store(&[5], ByteOrder::Big));
+		assert_eq!(quota.saved, 6);
+		assert!(!quota.store(&[6], ByteOrder::Little));
+		let mut block = [b' '; BLOCK_SIZE];
+		assert!(!quota.read_block(0, &mut block));
+		assert!(!quota.write_block(0, &block));
+	}
+}
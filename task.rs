@@ -0,0 +1,168 @@
+//! # Asynchronous Virtual Machine
+//!
+//! With the `tokio` feature enabled a virtual machine can be run as a future
+//! on an asynchronous runtime, with input and output coming from any pair of
+//! `AsyncRead` and `AsyncWrite` streams, such as the halves of a socket:
+//!
+//! ```no_run
+//! # extern crate embed;
+//! # extern crate tokio;
+//! # fn main() -> std::io::Result<()> {
+//! let runtime = tokio::runtime::Builder::new_current_thread().enable_io().build()?;
+//! let task = embed::Task::new(embed::VM::new(), tokio::io::stdin(), tokio::io::stdout());
+//! let code = runtime.block_on(task)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The instruction loop is run in slices of a limited number of cycles, the
+//! task yields back to the executor between them, so many virtual machines
+//! can share a single thread. When the virtual machine needs input and none
+//! is buffered the task waits on the input stream, and output is written out
+//! before any more instructions are executed.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use {Outcome, VM};
+
+/// `SLICE` is the default number of instructions executed before yielding.
+const SLICE: u64 = 100_000;
+
+/// `Task` is a future that runs a virtual machine until it halts, resolving
+/// to the value it halted with.
+pub struct Task<R, W> {
+	vm: VM,
+	reader: R,
+	writer: W,
+	slice: u64,
+	input: VecDeque<u8>,
+	output: Vec<u8>,
+	eof: bool,
+	code: Option<i32>,
+}
+
+impl<R, W> Task<R, W> where R: AsyncRead + Unpin, W: AsyncWrite + Unpin {
+	/// `new` creates a task running `vm`, reading from `reader` and writing
+	/// to `writer`, saving is not allowed.
+	pub fn new(vm: VM, reader: R, writer: W) -> Self {
+		Task { vm, reader, writer, slice: SLICE, input: VecDeque::new(), output: Vec::new(), eof: false, code: None }
+	}
+
+	/// `slice` sets the number of instructions executed before the task
+	/// yields to other tasks.
+	pub fn slice(mut self, cycles: u64) -> Self {
+		self.slice = cycles.max(1);
+		self
+	}
+
+	/// `into_inner` returns the virtual machine and the streams.
+	pub fn into_inner(self) -> (VM, R, W) {
+		(self.vm, self.reader, self.writer)
+	}
+
+	/// `flush` writes out all buffered output.
+	fn flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		while !self.output.is_empty() {
+			match ready!(Pin::new(&mut self.writer).poll_write(cx, &self.output))? {
+				0 => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+				n => { self.output.drain(..n); }
+			}
+		}
+		Pin::new(&mut self.writer).poll_flush(cx)
+	}
+
+	/// `fill` reads more input, noting the end of input.
+	fn fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let mut buffer = [0u8; 256];
+		let mut buffer = ReadBuf::new(&mut buffer);
+		ready!(Pin::new(&mut self.reader).poll_read(cx, &mut buffer))?;
+		if buffer.filled().is_empty() { self.eof = true }
+		self.input.extend(buffer.filled());
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<R, W> Future for Task<R, W> where R: AsyncRead + Unpin, W: AsyncWrite + Unpin {
+	type Output = io::Result<i32>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let task = self.get_mut();
+		loop {
+			ready!(task.flush(cx))?;
+			if let Some(code) = task.code { return Poll::Ready(Ok(code)) }
+
+			/* once all input has been read the image is given the end of it */
+			let outcome = if task.eof && task.input.is_empty() {
+				task.vm.step(task.slice, &mut (), &mut (), &mut task.output)
+			} else {
+				task.vm.step(task.slice, &mut (), &mut task.input, &mut task.output)
+			};
+			match outcome {
+				Outcome::Halted(code) => task.code = Some(code),
+				Outcome::NeedInput => {
+					if task.output.is_empty() && !task.eof { ready!(task.fill(cx))? }
+				}
+				Outcome::Running | Outcome::Timeout => { /* yield, writing out what output we can */
+					if let Poll::Ready(Err(e)) = task.flush(cx) { return Poll::Ready(Err(e)) }
+					cx.waker().wake_by_ref();
+					return Poll::Pending;
+				}
+			}
+		}
+	}
+}
+
+#[cfg(all(test, feature = "eforth"))]
+mod tests {
+	use super::*;
+	use std::io::prelude::*;
+	use std::net::{TcpListener, TcpStream};
+	use std::sync::{Arc, Barrier};
+	use std::thread;
+
+	const CONSOLES: usize = 4;
+
+	#[test]
+	fn consoles() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		let barrier = Arc::new(Barrier::new(CONSOLES));
+		let clients: Vec<_> = (0..CONSOLES).map(|i| {
+			let barrier = barrier.clone();
+			thread::spawn(move || {
+				let mut stream = TcpStream::connect(address).unwrap();
+				writeln!(stream, "decimal {} 100 + .", i).unwrap();
+				let expect = format!(" {}", i + 100);
+				let (mut output, mut buffer) = (Vec::new(), [0u8; 256]);
+				while !String::from_utf8_lossy(&output).contains(&expect) {
+					let n = stream.read(&mut buffer).unwrap();
+					assert!(n > 0, "connection closed early");
+					output.extend_from_slice(&buffer[..n]);
+				}
+				barrier.wait(); /* every console is running at the same time */
+				writeln!(stream, "{} (bye)", i).unwrap();
+				stream.read_to_end(&mut output).unwrap();
+			})
+		}).collect();
+
+		let runtime = tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap();
+		let _guard = runtime.enter();
+		let tasks: Vec<_> = (0..CONSOLES).map(|_| {
+			let (stream, _) = listener.accept().unwrap();
+			stream.set_nonblocking(true).unwrap();
+			let (reader, writer) = tokio::net::TcpStream::from_std(stream).unwrap().into_split();
+			runtime.spawn(Task::new(VM::new(), reader, writer).slice(1000))
+		}).collect();
+
+		let mut codes: Vec<i32> = tasks.into_iter().map(|t| runtime.block_on(t).unwrap().unwrap()).collect();
+		codes.sort();
+		assert_eq!(codes, [0, 1, 2, 3]);
+		for client in clients { client.join().unwrap() }
+	}
+}
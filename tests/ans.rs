@@ -0,0 +1,399 @@
+//! Runs the ANS Forth core tests, John Hayes' tester and core tests as kept
+//! in the Forth 2012 test suite, on the bundled eForth image and reports how
+//! many tests pass for each word tested. The tests are read from
+//! **tests/ans/core.fr**, "src/core.fr" of the test suite kept unchanged,
+//! and the test is skipped with a message if it is not there. The report is
+//! compared with **tests/ans/core.out**, which is rewritten instead when the
+//! environment variable `UPDATE_GOLDEN` is set, so a change in conformance,
+//! better or worse, shows up as a difference to review.
+//!
+//! eForth is case sensitive with lowercase names and reads lines of at most
+//! 80 characters, so the source is folded to lowercase, except for the text
+//! of strings and the arguments of `CHAR` and `[CHAR]`, comments are removed
+//! and long lines are split between words before the source is given to the
+//! image a line at a time. Tests that look up names built at run time, with
+//! `FIND` or `EVALUATE`, still see uppercase names and fail.
+
+extern crate embed;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use embed::{Input, Outcome, VM};
+
+/// `CYCLES` is the most instructions a line may take before the image is
+/// considered to have hung, which ends the run.
+const CYCLES: u64 = 10_000_000;
+
+/// `WIDTH` is the longest line eForth reads.
+const WIDTH: usize = 80;
+
+/// `SIGN_ON` starts what the image prints when it starts, which it does
+/// again if a test jumps to the start of memory.
+const SIGN_ON: &str = "eFORTH V";
+
+/// `Line` is input that is available only until the end of one line, so the
+/// virtual machine stops after each one and its output can be attributed.
+struct Line {
+	text: Vec<u8>,
+	at: usize,
+}
+
+impl Input for Line {
+	fn getc(&mut self) -> Option<u8> {
+		let c = self.text.get(self.at).cloned();
+		self.at += 1;
+		c
+	}
+
+	fn available(&mut self) -> bool { self.at < self.text.len() }
+}
+
+/// `Forth` is the image being fed the tests, with every line it was given
+/// so it can be restarted where it was if it halts or hangs.
+struct Forth {
+	vm: VM,
+	history: Vec<String>,
+}
+
+impl Forth {
+	/// `boot` starts the image and runs it until it waits for input.
+	fn boot() -> Forth {
+		let mut forth = Forth { vm: VM::new(), history: Vec::new() };
+		forth.step("");
+		forth
+	}
+
+	/// `step` runs the image on `line`, returning what it wrote.
+	fn step(&mut self, line: &str) -> (Outcome, String) {
+		let mut input = Line { text: line.as_bytes().to_vec(), at: 0 };
+		if !line.is_empty() {
+			input.text.push(b'\n');
+		}
+		let mut output = Vec::new();
+		let outcome = self.vm.step(CYCLES, &mut (), &mut input, &mut output);
+		(outcome, String::from_utf8_lossy(&output).into_owned())
+	}
+
+	/// `feed` gives the image `line` and returns what it wrote while reading
+	/// it. If instead it halts, hangs or restarts, a fresh image is given
+	/// every line before this one and why it stopped is returned.
+	fn feed(&mut self, line: &str) -> Result<String, String> {
+		let stopped = match self.step(line) {
+			(Outcome::NeedInput, ref output) if output.contains(SIGN_ON) => "Restarted".to_string(),
+			(Outcome::NeedInput, output) => {
+				self.history.push(line.to_string());
+				return Ok(output);
+			},
+			(outcome, _) => format!("{:?}", outcome),
+		};
+		self.vm = VM::new();
+		self.step("");
+		for line in self.history.clone() {
+			self.step(&line);
+		}
+		Err(stopped)
+	}
+}
+
+/// `ends` returns the length of the text a string word such as `S"` parses,
+/// up to and including its delimiter, or the rest of `rest` if it has none.
+fn ends(rest: &str, delimiter: char) -> usize {
+	rest.find(delimiter).map_or(rest.len(), |i| i + 1)
+}
+
+/// `fold` turns a line of the test source into the words eForth is given,
+/// removing comments, with `comment` tracking a parenthesised comment that
+/// carries on past the end of a line.
+fn fold(line: &str, comment: &mut bool) -> Vec<String> {
+	let mut words = Vec::new();
+	let mut rest = line;
+	loop {
+		if *comment {
+			match rest.find(')') {
+				Some(i) => { rest = &rest[i + 1..]; *comment = false; },
+				None => return words,
+			}
+		}
+		rest = rest.trim_start();
+		let word = match rest.split_whitespace().next() {
+			Some(word) => word,
+			None => return words,
+		};
+		rest = &rest[word.len()..];
+		let upper = word.to_uppercase();
+		let quoted = words.last().is_some_and(|w: &String| ["postpone", "[compile]", "'", "[']"].contains(&w.as_str()));
+		match upper.as_str() {
+			"\\" if !quoted => return words,
+			"(" if !quoted => { *comment = true; continue },
+			_ => {},
+		}
+		let mut folded = word.to_lowercase();
+		let delimiter = match upper.as_str() {
+			"S\"" | ".\"" | "C\"" | "ABORT\"" | "$\"" => Some('"'),
+			".(" => Some(')'),
+			_ => None,
+		};
+		if let Some(delimiter) = delimiter {
+			let text = &rest[1.min(rest.len())..];
+			let end = ends(text, delimiter);
+			folded.push(' ');
+			folded.push_str(&text[..end]);
+			rest = &text[end..];
+		} else if upper == "CHAR" || upper == "[CHAR]" {
+			if let Some(argument) = rest.split_whitespace().next() {
+				let start = rest.find(argument).unwrap();
+				folded.push(' ');
+				folded.push_str(argument);
+				rest = &rest[start + argument.len()..];
+			}
+		}
+		words.push(folded);
+	}
+}
+
+/// `wrap` joins `words` into lines no longer than eForth reads.
+fn wrap(words: &[String]) -> Vec<String> {
+	let mut lines = vec![String::new()];
+	for word in words {
+		let line = lines.last_mut().unwrap();
+		if !line.is_empty() && line.len() + 1 + word.len() > WIDTH {
+			lines.push(word.clone());
+		} else {
+			if !line.is_empty() {
+				line.push(' ');
+			}
+			line.push_str(word);
+		}
+	}
+	lines
+}
+
+/// `Verdict` is the outcome of a test, or of a line that is not a test.
+#[derive(Clone, Copy, PartialEq)]
+enum Verdict {
+	Pass,
+	Incorrect,
+	WrongNumber,
+	Error,
+	Stopped,
+}
+
+impl Verdict {
+	/// `of` classifies the output of a line, the tester prints a message for
+	/// each way a test can fail and eForth ends its errors with '?'.
+	fn of(output: &str) -> Verdict {
+		if output.contains("INCORRECT RESULT") {
+			Verdict::Incorrect
+		} else if output.contains("WRONG NUMBER OF RESULTS") {
+			Verdict::WrongNumber
+		} else if output.trim_end().ends_with('?') {
+			Verdict::Error
+		} else {
+			Verdict::Pass
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Verdict::Pass => "pass",
+			Verdict::Incorrect => "incorrect result",
+			Verdict::WrongNumber => "wrong number of results",
+			Verdict::Error => "error",
+			Verdict::Stopped => "stopped",
+		}
+	}
+}
+
+/// `Group` is the tests following a `TESTING` line, which names the words
+/// they test.
+struct Group {
+	title: String,
+	words: Vec<String>,
+	tests: Vec<(Vec<String>, Verdict)>,
+}
+
+/// `Report` collects the results of every test.
+struct Report {
+	groups: Vec<Group>,
+	failures: Vec<String>,
+	setup: Vec<String>,
+}
+
+impl Report {
+	/// `testing` starts a new group from the text after `TESTING`, in which
+	/// labels such as "BOOLEANS:" are not words.
+	fn testing(&mut self, title: &str) {
+		let words = title.split_whitespace()
+			.filter(|w| w.len() == 1 || !w.ends_with(':'))
+			.map(|w| w.to_uppercase())
+			.collect();
+		self.groups.push(Group { title: title.to_string(), words, tests: Vec::new() });
+	}
+
+	/// `test` records the `verdict` of the test made of `source` lines, with
+	/// `detail` of why it failed.
+	fn test(&mut self, source: &[String], verdict: Verdict, detail: &str) {
+		let words = source.iter().flat_map(|l| l.split_whitespace()).map(|w| w.to_uppercase()).collect();
+		if self.groups.is_empty() {
+			self.testing("");
+		}
+		self.groups.last_mut().unwrap().tests.push((words, verdict));
+		if verdict != Verdict::Pass {
+			let mut failure = format!("{}: {}", verdict.name(), source.join(" ... "));
+			if !detail.is_empty() {
+				failure += &format!(" ({})", detail);
+			}
+			self.failures.push(failure);
+		}
+	}
+
+	/// `render` prints the number of tests passed by group, by word and in
+	/// total, then the failures.
+	fn render(&self) -> String {
+		let pass = |tests: &mut dyn Iterator<Item = &(Vec<String>, Verdict)>| {
+			let (mut passed, mut total) = (0, 0);
+			for &(_, verdict) in tests {
+				total += 1;
+				if verdict == Verdict::Pass {
+					passed += 1;
+				}
+			}
+			format!("{:>4}/{:<4}", passed, total)
+		};
+		let mut out = String::from("ANS Forth core tests on the bundled eForth image\n\nBy group:\n");
+		for group in self.groups.iter().filter(|g| !g.tests.is_empty()) {
+			out += &format!("{} {}\n", pass(&mut group.tests.iter()), group.title);
+		}
+		out += "\nBy word:\n";
+		for group in &self.groups {
+			for word in &group.words {
+				let mut tests = group.tests.iter().filter(|t| t.0.contains(word)).peekable();
+				if tests.peek().is_some() {
+					out += &format!("{} {}\n", pass(&mut tests), word);
+				}
+			}
+		}
+		out += &format!("\nTotal:\n{}\n", pass(&mut self.groups.iter().flat_map(|g| g.tests.iter())));
+		out += "\nFailed tests:\n";
+		for failure in &self.failures {
+			out += &format!("{}\n", failure);
+		}
+		out += "\nErrors outside of tests:\n";
+		for line in &self.setup {
+			out += &format!("{}\n", line);
+		}
+		out
+	}
+}
+
+/// `code` is the error code eForth printed at the end of `output`.
+fn code(output: &str) -> &str {
+	output.split_whitespace().last().unwrap_or("")
+}
+
+/// `run` feeds the tester, then the tests, to a fresh image. After an error
+/// in a colon definition that carries on past the line, the rest of it is
+/// skipped rather than interpreted, up to its end or the next definition,
+/// test or group.
+fn run(tester: &str, tests: &str) -> Report {
+	let mut forth = Forth::boot();
+	let mut report = Report { groups: Vec::new(), failures: Vec::new(), setup: Vec::new() };
+	let mut comment = false;
+	for line in tester.lines() {
+		for line in wrap(&fold(line, &mut comment)) {
+			let output = forth.feed(&line).unwrap_or_else(|o| panic!("tester: {:?} gave {:?}", line, o));
+			assert!(Verdict::of(&output) == Verdict::Pass, "tester: {:?} gave {:?}", line, output);
+		}
+	}
+
+	let mut pending: Vec<String> = Vec::new();
+	let mut output = String::new();
+	let mut stopped = None;
+	let (mut open, mut skipping) = (false, false);
+	for line in tests.lines() {
+		let words = fold(line, &mut comment);
+		if words.first().is_some_and(|w| w == "testing") {
+			report.testing(line.trim_start()[7..].trim());
+		}
+		if words.is_empty() {
+			continue;
+		}
+		let testing = !pending.is_empty() || words.first().is_some_and(|w| w == "t{");
+		for line in wrap(&words) {
+			let closes = line.split_whitespace().any(|w| w == ";");
+			let ends = line.split_whitespace().any(|w| w == "}t");
+			let first = line.split_whitespace().next().unwrap_or("");
+			skipping &= ![":", "t{", "testing"].contains(&first);
+			if skipping {
+				report.setup.push(format!("{} (skipped)", line));
+				skipping = !closes;
+				continue;
+			}
+			let out = match forth.feed(&line) {
+				Ok(out) => out,
+				Err(reason) => {
+					if !testing {
+						report.setup.push(format!("{} ({})", line, reason));
+					}
+					stopped = Some(reason);
+					open = false;
+					pending.push(line);
+					break;
+				},
+			};
+			open = line.split_whitespace().rev().find(|&w| w == ":" || w == ";").map_or(open, |w| w == ":");
+			if testing {
+				pending.push(line);
+				output += &out;
+			} else if Verdict::of(&out) == Verdict::Error {
+				report.setup.push(format!("{} ({})", line, code(&out)));
+			}
+			if Verdict::of(&out) == Verdict::Error {
+				skipping = open && !ends;
+				open = false;
+			}
+		}
+		if let Some(reason) = stopped.take() {
+			if testing {
+				report.test(&pending, Verdict::Stopped, &reason);
+			}
+			pending.clear();
+			output.clear();
+		} else if testing && (words.iter().any(|w| w == "}t") || Verdict::of(&output) == Verdict::Error) {
+			let verdict = Verdict::of(&output);
+			report.test(&pending, verdict, if verdict == Verdict::Error { code(&output) } else { "" });
+			pending.clear();
+			output.clear();
+		}
+	}
+	report
+}
+
+#[test]
+fn core() {
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("ans");
+	let tester = fs::read_to_string(directory.join("tester.fth")).unwrap();
+	let tests = match fs::read_to_string(directory.join("core.fr")) {
+		Ok(tests) => tests,
+		Err(e) => {
+			eprintln!("skipped, tests/ans/core.fr: {}, it should be src/core.fr of the Forth 2012 test suite, unchanged", e);
+			return;
+		},
+	};
+	let report = run(&tester, &tests);
+	let rendered = report.render();
+	println!("{}", rendered);
+
+	let total: usize = report.groups.iter().map(|g| g.tests.len()).sum();
+	assert!(total > 300, "only {} tests were found", total);
+
+	let golden = directory.join("core.out");
+	if env::var_os("UPDATE_GOLDEN").is_some() {
+		fs::write(&golden, &rendered).unwrap();
+		return;
+	}
+	let expected = fs::read_to_string(&golden).unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_GOLDEN set to create it", golden.display(), e));
+	assert!(rendered == expected, "the report differs from {}, run with UPDATE_GOLDEN set to update it", golden.display());
+}
@@ -0,0 +1,51 @@
+//! Golden tests of the bundled eForth image, each script in **tests/golden**
+//! is run on a fresh virtual machine and its output compared with the file
+//! of the same name ending in ".out". Setting the environment variable
+//! `UPDATE_GOLDEN`, as "make golden" does, writes the output to the golden
+//! files instead, which should be reviewed before they are committed, for
+//! example when replacing the image.
+
+extern crate embed;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use embed::VM;
+
+/// `run` runs `script` on the eForth image until it reaches the end of its
+/// input, which halts it.
+fn run(script: &[u8]) -> Vec<u8> {
+	let mut vm = VM::new();
+	let mut output = Vec::new();
+	vm.execute(&mut (), &mut &script[..], &mut output);
+	output
+}
+
+#[test]
+fn golden() {
+	let update = env::var_os("UPDATE_GOLDEN").is_some();
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+	let mut scripts: Vec<_> = fs::read_dir(&directory).unwrap()
+		.map(|e| e.unwrap().path())
+		.filter(|p| p.extension().is_some_and(|e| e == "fth"))
+		.collect();
+	scripts.sort();
+	assert!(!scripts.is_empty());
+
+	let mut failed = Vec::new();
+	for script in &scripts {
+		let output = run(&fs::read(script).unwrap());
+		let golden = script.with_extension("out");
+		if update {
+			fs::write(&golden, &output).unwrap();
+			continue;
+		}
+		let expected = fs::read(&golden).unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_GOLDEN set to create it", golden.display(), e));
+		if output != expected {
+			eprintln!("{} differs, expected:\n{}\ngot:\n{}", script.display(), String::from_utf8_lossy(&expected), String::from_utf8_lossy(&output));
+			failed.push(script.display().to_string());
+		}
+	}
+	assert!(failed.is_empty(), "output differs for {:?}", failed);
+}
@@ -0,0 +1,195 @@
+//! # Ahead of Time Translation
+//!
+//! `translate` turns an image into Rust source, a module with one function
+//! per basic block of the code in the image, which runs on an `aot::Machine`
+//! with the same semantics as `VM::step`. The module has:
+//!
+//! * `CODE`, a bit for each cell that has been translated
+//! * `IMAGE`, the cells as they were translated, up to the last translated one
+//! * `run`, which runs a machine until it halts or needs input, calling the
+//!   function for the block starting at the program counter, or falling back
+//!   to the interpreter for instructions that are not in any block
+//! * `execute`, which runs a virtual machine as `VM::execute` does
+//!
+//! Basic blocks start at the entry points, the reset vector at zero and the
+//! trap handler at one, at the targets of calls and branches and after each
+//! call, 0branch and division, and are found by following the code from the
+//! entry points. Words only reached through `execute` or return stack tricks,
+//! and any compiled at run time, are left to the interpreter. If a
+//! translated cell is written to, or no longer matches `IMAGE` when a machine
+//! is created, translated code is no longer used.
+//!
+//! This file only uses the standard library, as it is included by `build.rs`
+//! to translate the bundled eForth image.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// `Flow` is what an instruction does to the program counter.
+enum Flow {
+	/// Carries on to the next instruction
+	Next,
+	/// Carries on to the next instruction, or traps on division by zero
+	Divide,
+	/// Jumps to a target, and carries on after it if `fall` is set
+	Jump { target: u16, fall: bool },
+	/// Returns, halts, or otherwise jumps somewhere not known in advance
+	Stop,
+}
+
+/// `flow` classifies `instruction`, see `VM::step` for the encoding.
+fn flow(instruction: u16) -> Flow {
+	let target = instruction & 0x1fff;
+	if instruction & 0x8000 != 0 { return Flow::Next }
+	match instruction & 0xe000 {
+		0x6000 if instruction & 0x10 != 0 => Flow::Stop,
+		0x6000 => match (instruction >> 8) & 0x1f {
+			25 | 26 => Flow::Divide,
+			27 => Flow::Stop,
+			_ => Flow::Next,
+		},
+		0x4000 | 0x2000 => Flow::Jump { target, fall: true },
+		_ => Flow::Jump { target, fall: false },
+	}
+}
+
+/// `blocks` finds the basic blocks of the code reachable in `cells`.
+///
+/// # Returns
+///
+/// The first and last cell of each block.
+pub fn blocks(cells: &[u16]) -> Vec<(u16, u16)> {
+	let valid = |pc: u16| (pc as usize) < cells.len();
+	let mut leaders: BTreeSet<u16> = [0, 1].iter().cloned().filter(|&pc| valid(pc)).collect();
+	let mut code = vec![false; cells.len()];
+	let mut work: Vec<u16> = leaders.iter().cloned().collect();
+	while let Some(start) = work.pop() {
+		let mut pc = start;
+		while valid(pc) {
+			if code[pc as usize] { /* ran into another block, which has to be split */
+				if pc != start { leaders.insert(pc); }
+				break;
+			}
+			code[pc as usize] = true;
+			let mut leader = |pc: u16, leaders: &mut BTreeSet<u16>| if valid(pc) && leaders.insert(pc) { work.push(pc) };
+			match flow(cells[pc as usize]) {
+				Flow::Next => { pc += 1; continue }
+				Flow::Divide => leader(pc + 1, &mut leaders),
+				Flow::Jump { target, fall } => {
+					leader(target, &mut leaders);
+					if fall { leader(pc + 1, &mut leaders) }
+				}
+				Flow::Stop => { }
+			}
+			break;
+		}
+	}
+
+	let mut blocks = Vec::new();
+	for &start in &leaders {
+		let mut end = start;
+		while let Flow::Next = flow(cells[end as usize]) {
+			if !valid(end + 1) || leaders.contains(&(end + 1)) { break }
+			end += 1;
+		}
+		blocks.push((start, end));
+	}
+	blocks
+}
+
+/// `translate` turns the image in `cells` into the source of a Rust module,
+/// as described above. The module uses `Machine` and the other types from the
+/// path `runtime`, which is `embed::aot` outside of this library.
+///
+/// # Example
+///
+/// ```
+/// let source = embed::aot::translate(&[0x8005, 0x7b00], "embed::aot");
+/// assert!(source.contains("pub fn run(m: &mut Machine)"));
+/// ```
+pub fn translate(cells: &[u16], runtime: &str) -> String {
+	let blocks = blocks(cells);
+	let mut code = [0u64; 0x8000 / 64];
+	for &(start, end) in &blocks {
+		for pc in start..=end { code[pc as usize / 64] |= 1 << (pc % 64) }
+	}
+
+	let mut s = String::new();
+	let _ = writeln!(s, "// Translated from an image of {} cells by `translate`, do not edit.", cells.len());
+	let _ = writeln!(s);
+	let _ = writeln!(s, "use {}::{{Input, Machine, Outcome, Output, Store, VM}};", runtime);
+	let _ = writeln!(s);
+	let _ = writeln!(s, "/// `CODE` has a bit set for each cell that has been translated.");
+	let _ = writeln!(s, "pub static CODE: [u64; {}] = [", code.len());
+	for line in code.chunks(4) {
+		let line: Vec<String> = line.iter().map(|c| format!("0x{:016x}", c)).collect();
+		let _ = writeln!(s, "\t{},", line.join(", "));
+	}
+	let _ = writeln!(s, "];");
+	let _ = writeln!(s);
+	let used = blocks.last().map_or(0, |&(_, end)| end as usize + 1);
+	let _ = writeln!(s, "/// `IMAGE` holds the cells as they were when they were translated.");
+	let _ = writeln!(s, "pub static IMAGE: [u16; {}] = [", used);
+	for line in cells[..used].chunks(8) {
+		let line: Vec<String> = line.iter().map(|c| format!("0x{:04x}", c)).collect();
+		let _ = writeln!(s, "	{},", line.join(", "));
+	}
+	let _ = writeln!(s, "];");
+	let _ = writeln!(s);
+	let _ = writeln!(s, "/// `execute` runs `vm` until it halts or needs input, as `VM::execute`.");
+	let _ = writeln!(s, "pub fn execute(vm: &mut VM, store: &mut dyn Store, input: &mut dyn Input, output: &mut dyn Output) -> Outcome {{");
+	let _ = writeln!(s, "\tlet mut m = Machine::new(vm, &CODE, &IMAGE, store, input, output);");
+	let _ = writeln!(s, "\trun(&mut m);");
+	let _ = writeln!(s, "\tm.finish()");
+	let _ = writeln!(s, "}}");
+	let _ = writeln!(s);
+	let _ = writeln!(s, "/// `run` runs `m` until it halts or needs input.");
+	let _ = writeln!(s, "pub fn run(m: &mut Machine) {{");
+	let _ = writeln!(s, "\twhile m.running() {{");
+	let _ = writeln!(s, "\t\tif !m.translated() {{ m.interpret(); continue }}");
+	let _ = writeln!(s, "\t\tmatch m.pc() {{");
+	for &(start, _) in &blocks {
+		let _ = writeln!(s, "\t\t\t0x{:04x} => b{:04x}(m),", start, start);
+	}
+	let _ = writeln!(s, "\t\t\t_ => m.interpret(),");
+	let _ = writeln!(s, "\t\t}}");
+	let _ = writeln!(s, "\t}}");
+	let _ = writeln!(s, "}}");
+	for &(start, end) in &blocks {
+		let _ = writeln!(s);
+		let _ = writeln!(s, "fn b{:04x}(m: &mut Machine) {{", start);
+		for pc in start..end {
+			let _ = writeln!(s, "\tif m.op(0x{:04x}) {{ return }}", cells[pc as usize]);
+		}
+		let _ = writeln!(s, "\tm.op(0x{:04x});", cells[end as usize]);
+		let _ = writeln!(s, "}}");
+	}
+	s
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn basic_blocks() {
+		let cells = [
+			0x0002,         /* 0: branch 2 */
+			0x7b00,         /* 1: bye */
+			0x8001, 0x4007, /* 2: 1 call 7 */
+			0x2002,         /* 4: 0branch 2 */
+			0x6000, 0x7b00, /* 5: bye */
+			0x6b1c,         /* 7: 1- exit */
+			0x1234,         /* 8: data */
+		];
+		assert_eq!(blocks(&cells), [(0, 0), (1, 1), (2, 3), (4, 4), (5, 6), (7, 7)]);
+
+		/* a jump into the middle of a block splits it */
+		let cells = [0x8001, 0x8002, 0x8003, 0x0002];
+		assert_eq!(blocks(&cells), [(0, 0), (1, 1), (2, 3)]);
+		let source = translate(&cells, "embed::aot");
+		assert!(source.contains("fn b0002(m: &mut Machine) {\n\tif m.op(0x8003) { return }\n\tm.op(0x0002);\n}"));
+		assert!(source.contains("\t0x000000000000000f, 0x0000000000000000,"));
+		assert!(source.contains("pub static IMAGE: [u16; 4] = [\n\t0x8001, 0x8002, 0x8003, 0x0002,\n];"));
+	}
+}
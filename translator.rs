@@ -0,0 +1,55 @@
+//! # eForth Image Translator
+//!
+//! Translates an image into the source of a Rust module, one function per
+//! basic block, which runs on `embed::aot::Machine`. The module is written
+//! to standard output, or to a file if one is named, and can be included in
+//! a program that depends on this library, as in "eforth-aot app.blk app.rs".
+//! The image may be in any of the formats `VM::load_from` accepts, chosen by
+//! file extension. "-r" sets the path the module imports the runtime from,
+//! "embed::aot" by default.
+
+extern crate embed;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use embed::{Format, VM};
+
+const USAGE: &str = "usage: eforth-aot [-r runtime] image [output.rs]";
+
+fn fail(message: &str) -> ! {
+	eprintln!("{}\n{}", message, USAGE);
+	process::exit(1);
+}
+
+fn main() {
+	let mut runtime = "embed::aot".to_string();
+	let mut names = Vec::new();
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-r" => runtime = args.next().unwrap_or_else(|| fail("expected a runtime path")),
+			"-h" => fail("translates an image into a Rust module"),
+			_ => names.push(arg),
+		}
+	}
+	if names.is_empty() || names.len() > 2 { fail("expected an image") }
+
+	let name = &names[0];
+	let format = Path::new(name).extension().and_then(|e| e.to_str()).and_then(Format::from_extension).unwrap_or(Format::Raw);
+	let mut file = File::open(name).unwrap_or_else(|e| fail(&format!("failed to open \"{}\": {}", name, e)));
+	let mut vm = VM::new();
+	let length = vm.load_from(&mut file, format).unwrap_or_else(|e| fail(&format!("failed to load \"{}\": {}", name, e)));
+
+	let cells: Vec<u16> = (0..length).map(|a| vm.read(a)).collect();
+	let source = embed::aot::translate(&cells, &runtime);
+	let written = match names.get(1) {
+		Some(output) => fs::write(output, source),
+		None => io::stdout().write_all(source.as_bytes()),
+	};
+	if let Err(e) = written { fail(&format!("failed to write: {}", e)) }
+}
@@ -0,0 +1,27 @@
+// Boots the built in eForth image and evaluates "words", run with "node
+// test.js" after building the package with "wasm-pack build --target nodejs".
+const assert = require('assert');
+const { Console } = require('./pkg/embed_wasm.js');
+
+const decoder = new TextDecoder();
+const c = new Console();
+
+function run(input) {
+	if (input)
+		c.feed(new TextEncoder().encode(input));
+	let output = '';
+	for (let i = 0; i < 100 && !c.step(1000000); i++)
+		output += decoder.decode(c.drain());
+	return output + decoder.decode(c.drain());
+}
+
+assert.ok(run().includes('eFORTH'));
+const words = run('words\n');
+assert.ok(words.includes('dup') && words.includes('words'));
+
+const image = c.save();
+run('bye\n');
+assert.strictEqual(c.exit_code(), 0);
+c.load(image);
+assert.strictEqual(c.exit_code(), undefined);
+console.log('ok');
@@ -0,0 +1,120 @@
+//! # WebAssembly Bindings for the Embed Virtual Machine
+//!
+//! `Console` wraps the virtual machine for use from JavaScript, such as for
+//! an in browser Forth console. Nothing blocks, input is fed in to a buffer,
+//! the virtual machine is run for a number of cycles at a time, stopping early
+//! if it runs out of input, and output is drained from another buffer. Build
+//! with "wasm-pack build --target nodejs" (or "--target web") from this
+//! directory, "node test.js" then runs a quick test of the package.
+//!
+//! The bindings are only generated when compiling for WebAssembly, on other
+//! targets this is a plain Rust library, which is how it is unit tested.
+
+extern crate embed;
+#[cfg(target_arch = "wasm32")]
+extern crate wasm_bindgen;
+
+use std::collections::VecDeque;
+
+use embed::{Outcome, VM};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// `Console` is a virtual machine along with its input and output buffers.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct Console {
+	vm: VM,
+	input: VecDeque<u8>,
+	output: Vec<u8>,
+	code: Option<i32>,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Console {
+	/// `new` creates a console running the built in eForth image.
+	#[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+	#[allow(clippy::new_without_default)]
+	pub fn new() -> Console {
+		Console { vm: VM::new(), input: VecDeque::new(), output: Vec::new(), code: None }
+	}
+
+	/// `from_image` creates a console running the image in `bytes`.
+	pub fn from_image(bytes: &[u8]) -> Result<Console, String> {
+		let vm = VM::from_bytes(bytes).map_err(|e| e.to_string())?;
+		Ok(Console { vm, input: VecDeque::new(), output: Vec::new(), code: None })
+	}
+
+	/// `feed` appends `bytes` to the input buffer.
+	pub fn feed(&mut self, bytes: &[u8]) {
+		self.input.extend(bytes);
+	}
+
+	/// `drain` removes and returns everything in the output buffer.
+	pub fn drain(&mut self) -> Vec<u8> {
+		std::mem::take(&mut self.output)
+	}
+
+	/// `step` runs the virtual machine for at most `cycles` instructions, it
+	/// stops early if the virtual machine halts or needs more input.
+	///
+	/// # Returns
+	///
+	/// True if the virtual machine has halted, see `exit_code`.
+	pub fn step(&mut self, cycles: u32) -> bool {
+		if self.code.is_none() {
+			if let Outcome::Halted(r) = self.vm.step(cycles as u64, &mut (), &mut self.input, &mut self.output) {
+				self.code = Some(r);
+			}
+		}
+		self.code.is_some()
+	}
+
+	/// `exit_code` is the value the virtual machine halted with, if it has.
+	pub fn exit_code(&self) -> Option<i32> {
+		self.code
+	}
+
+	/// `save` returns the memory of the virtual machine as an image.
+	pub fn save(&self) -> Vec<u8> {
+		let mut image = Vec::new();
+		self.vm.save(&mut image);
+		image
+	}
+
+	/// `load` replaces the image being run, the input and output buffers are
+	/// left as they are.
+	pub fn load(&mut self, bytes: &[u8]) -> Result<(), String> {
+		self.vm = VM::from_bytes(bytes).map_err(|e| e.to_string())?;
+		self.code = None;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn console() {
+		let mut c = Console::new();
+		assert!(!c.step(10_000_000));
+		let banner = String::from_utf8(c.drain()).unwrap();
+		assert!(banner.contains("eFORTH"));
+		assert!(!c.step(10_000_000));
+		assert!(c.drain().is_empty());
+
+		c.feed(b"words\n");
+		assert!(!c.step(10_000_000));
+		let words = String::from_utf8(c.drain()).unwrap();
+		assert!(words.contains("dup"));
+
+		let image = c.save();
+		c.feed(b"bye\n");
+		assert!(c.step(10_000_000));
+		assert_eq!(c.exit_code(), Some(0));
+
+		c.load(&image).unwrap();
+		assert!(c.exit_code().is_none());
+		assert!(c.load(b"not an image").is_err());
+	}
+}